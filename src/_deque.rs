@@ -0,0 +1,81 @@
+use std::collections::VecDeque;
+
+/// protocol for seqs which can grow and shrink at both ends.
+///
+/// # example
+/// ```
+/// use protocoll::Deque;
+/// use std::collections::VecDeque;
+/// let q = VecDeque::new()
+///     .inc_back(1).inc_back(2)
+///     .inc_front(0).dec_back();
+/// assert_eq!(q.peek_front(), Some(&0));
+/// assert_eq!(q.peek_back(), Some(&1));
+/// ```
+pub trait Deque<T> where Self:Sized {
+    /// adds item `i` to the front.
+    fn inc_front(self, i:T) -> Self;
+
+    /// adds item `i` to the back.
+    fn inc_back(self, i:T) -> Self;
+
+    /// removes the first item.
+    fn dec_front(self) -> Self;
+
+    /// removes the last item.
+    fn dec_back(self) -> Self;
+
+    /// the first item.
+    ///
+    /// like `clojure`'s [`peek`](http://clojuredocs.org/clojure.core/peek)
+    /// for lists and queues.
+    fn peek_front(&self) -> Option<&T>;
+
+    /// the last item.
+    ///
+    /// like `clojure`'s [`peek`](http://clojuredocs.org/clojure.core/peek)
+    /// for vectors.
+    fn peek_back(&self) -> Option<&T>;
+}
+
+impl<T> Deque<T> for VecDeque<T> {
+    fn inc_front(mut self, i:T) -> Self
+    {self.push_front(i); self}
+
+    fn inc_back(mut self, i:T) -> Self
+    {self.push_back(i); self}
+
+    fn dec_front(mut self) -> Self
+    {self.pop_front(); self}
+
+    fn dec_back(mut self) -> Self
+    {self.pop_back(); self}
+
+    fn peek_front(&self) -> Option<&T>
+    {self.front()}
+
+    fn peek_back(&self) -> Option<&T>
+    {self.back()}
+}
+
+/// the back is cheap. `inc_front` and `dec_front` are O(len), caused by
+/// shifting all items; use a `VecDeque` if the front is busy.
+impl<T> Deque<T> for Vec<T> {
+    fn inc_front(mut self, i:T) -> Self
+    {self.insert(0,i); self}
+
+    fn inc_back(mut self, i:T) -> Self
+    {self.push(i); self}
+
+    fn dec_front(mut self) -> Self
+    {if !self.is_empty() {self.remove(0);} self}
+
+    fn dec_back(mut self) -> Self
+    {self.pop(); self}
+
+    fn peek_front(&self) -> Option<&T>
+    {self.first()}
+
+    fn peek_back(&self) -> Option<&T>
+    {self.last()}
+}
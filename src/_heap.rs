@@ -0,0 +1,109 @@
+use _seq::Seq;
+use min_heap::MinHeap;
+use std::collections::BinaryHeap;
+use std::marker::PhantomData;
+
+/// protocol for priority queues, where [`Seq::dec`](trait.Seq.html#tymethod.dec)
+/// always removes the item with the highest priority. for `BinaryHeap` it's the
+/// greatest one; for [`MinHeap`](seq/struct.MinHeap.html) the least.
+///
+/// # example
+/// ```
+/// use protocoll::{Heap,Seq};
+/// use std::collections::BinaryHeap;
+/// let h = BinaryHeap::new().plus(vec![3,1,4,1,5]);
+/// assert_eq!(h.peek(), Some(&5));
+/// assert_eq!(h.fun_sorted()(1), Some(&4));
+/// assert_eq!(h.into_sorted().collect::<Vec<_>>(), [5,4,3,1,1]);
+/// ```
+pub trait Heap<T>: Seq<T> {
+    /// the item to be removed next.
+    ///
+    /// like `clojure`'s [`peek`](http://clojuredocs.org/clojure.core/peek).
+    fn peek(&self) -> Option<&T>;
+
+    /// removes and returns the item with the highest priority.
+    fn pop_top(&mut self) -> Option<T>;
+
+    /// a heap maps from ranks to items in priority order. sorts once and then
+    /// O(1) per call, unlike [`Seq::fun`](trait.Seq.html#tymethod.fun) which
+    /// indexes in an arbitrary order.
+    fn fun_sorted<'a>(&'a self) -> Box<dyn Fn(usize) -> Option<&'a T> + 'a>;
+
+    /// iterate in priority order while consuming the heap. O(log(len)) per
+    /// item, so taking only the first few items is cheap.
+    fn into_sorted(self) -> IntoSorted<T,Self>
+    {IntoSorted(self, PhantomData)}
+
+    /// like [`into_sorted`](#method.into_sorted) but leaves an empty heap
+    /// behind. dropping the iterator early drops the remaining items.
+    fn drain_sorted(&mut self) -> DrainSorted<'_,T,Self>
+    {DrainSorted(self, PhantomData)}
+}
+
+pub struct IntoSorted<T,H>(H, PhantomData<T>);
+
+impl<T,H> IntoSorted<T,H> {
+    /// the remaining heap.
+    pub fn into_inner(self) -> H
+    {self.0}
+}
+
+impl<T,H> Iterator for IntoSorted<T,H> where H:Heap<T>
+{type Item = T;
+ fn next(&mut self) -> Option<T> {self.0.pop_top()}}
+
+pub struct DrainSorted<'a,T,H:'a+Heap<T>>(&'a mut H, PhantomData<T>);
+
+impl<'a,T,H> Iterator for DrainSorted<'a,T,H> where H:Heap<T>
+{type Item = T;
+ fn next(&mut self) -> Option<T> {self.0.pop_top()}}
+
+impl<'a,T,H> Drop for DrainSorted<'a,T,H> where H:Heap<T>
+{fn drop(&mut self) {for _ in self {}}}
+
+impl<T> Heap<T> for BinaryHeap<T> where T:Ord {
+    fn peek(&self) -> Option<&T>
+    {BinaryHeap::peek(self)}
+
+    fn pop_top(&mut self) -> Option<T>
+    {self.pop()}
+
+    fn fun_sorted<'a>(&'a self) -> Box<dyn Fn(usize) -> Option<&'a T> + 'a>
+    {let mut v:Vec<&T> = self.iter().collect();
+     v.sort_by(|a,b| b.cmp(a));
+     Box::new(move |i| v.get(i).cloned())}
+}
+
+/// room for the `k` items kept and the one pushed before popping, but no more
+/// than `coll` surely has.
+fn capacity<I>(k:usize, coll:&I) -> usize where I:Iterator
+{k.min(coll.size_hint().0).saturating_add(1)}
+
+/// the `k` greatest items of `coll`, greatest first, poured into a new seq.
+/// O(len(coll) * log(k)).
+///
+/// # example
+/// ```
+/// use protocoll::seq::{top_k,bottom_k};
+/// let v:Vec<_> = top_k(2, vec![3,1,4,1,5]);
+/// assert_eq!(v, [5,4]);
+/// let v:Vec<_> = bottom_k(3, vec![3,1,4,1,5]);
+/// assert_eq!(v, [1,1,3]);
+/// let v:Vec<_> = top_k(usize::MAX, vec![3,1,4]);
+/// assert_eq!(v, [4,3,1]);
+/// ```
+pub fn top_k<S,T,I>(k:usize, coll:I) -> S where S:Seq<T>+Default, I:IntoIterator<Item = T>, T:Ord
+{let coll = coll.into_iter();
+ let mut h = MinHeap::with_capacity(capacity(k, &coll));
+ for t in coll {h.push(t); if h.len() > k {h.pop();}}
+ let mut v:Vec<T> = h.into_sorted().collect();
+ v.reverse(); S::default().plus(v)}
+
+/// the `k` least items of `coll`, least first, poured into a new seq.
+/// O(len(coll) * log(k)).
+pub fn bottom_k<S,T,I>(k:usize, coll:I) -> S where S:Seq<T>+Default, I:IntoIterator<Item = T>, T:Ord
+{let coll = coll.into_iter();
+ let mut h = BinaryHeap::with_capacity(capacity(k, &coll));
+ for t in coll {h.push(t); if h.len() > k {h.pop();}}
+ S::default().plus(h.into_sorted_vec())}
@@ -1,7 +1,8 @@
+use transducer::{Transducer,Reducer,MapInc,transduce};
 use std::collections::{HashMap,hash_map,BTreeMap,btree_map};
 use std::borrow::Borrow;
 use std::hash::Hash;
-use std::iter::FromIterator;
+use std::iter::{FromIterator,Peekable};
 
 /// basic protocol for maps.
 pub trait Map<K,V> where Self:Sized {
@@ -24,6 +25,38 @@ pub trait Map<K,V> where Self:Sized {
     fn plus<I>(self, coll:I) -> Self where I:IntoIterator<Item = (K,V)>
     {coll.into_iter().fold(self, |m,(k,v)| Map::inc(m,k,v))}
 
+    /// pours `coll` into this one while `p` holds for the map so far and the
+    /// next entry, e.g. until it hits a size budget or a sentinel appears.
+    /// returns the map, how many entries were poured, and the rest of `coll`,
+    /// which starts with the entry that stopped it.
+    ///
+    /// # example
+    /// ```
+    /// use protocoll::Map;
+    /// use std::collections::HashMap;
+    /// let (m, n, mut rest) = HashMap::new().plus_while
+    ///     ([1,1,2,3,4].iter().map(|&k| (k, ())), |m,_| m.len() < 3);
+    /// assert_eq!((m.len(), n), (3, 4));
+    /// assert_eq!(rest.next(), Some((4, ())));
+    /// let (_, n, _) = HashMap::new().plus_while(vec![(1,'a'), (0,'$'), (2,'b')], |_,&(_,v)| v != '$');
+    /// assert_eq!(n, 1);
+    /// ```
+    fn plus_while<I,P>(self, coll:I, mut p:P) -> (Self, usize, Peekable<I::IntoIter>)
+        where I:IntoIterator<Item = (K,V)>, P:FnMut(&Self, &(K,V)) -> bool
+    {let mut rest = coll.into_iter().peekable();
+     let (mut m, mut n) = (self, 0);
+     while let Some((k,v)) = rest.next_if(|kv| p(&m, kv)) {m = Map::inc(m, k, v); n += 1}
+     (m, n, rest)}
+
+    /// pours another collection through the transducer `xform` into this
+    /// one. the transducer must make `(K,V)` entries.
+    ///
+    /// like `clojure`'s [`into`](http://clojuredocs.org/clojure.core/into)
+    /// with a transducer.
+    fn into_with<X,I>(self, xform:X, coll:I) -> Self
+        where I:IntoIterator, X:Transducer<MapInc>, X::Reducer:Reducer<Self,I::Item>
+    {transduce(xform, MapInc, self, coll)}
+
     /// `clear`.
     fn zero(self) -> Self;
 
@@ -81,6 +114,16 @@ pub trait Map<K,V> where Self:Sized {
     /// ```
     fn merge<I,F>(self, coll:I, mut f:F) -> Self where I:IntoIterator<Item = (K,V)>, F:FnMut(V,V) -> V
     {coll.into_iter().fold(self, |m,(k,v)| Map::update(m, k, |opt_u| match opt_u {Some(u) => f(u,v), None => v}))}
+
+    /// like [`merge`](#method.merge) but only while `p` holds, as with
+    /// [`plus_while`](#method.plus_while).
+    fn merge_while<I,F,P>(self, coll:I, mut f:F, mut p:P) -> (Self, usize, Peekable<I::IntoIter>)
+        where I:IntoIterator<Item = (K,V)>, F:FnMut(V,V) -> V, P:FnMut(&Self, &(K,V)) -> bool
+    {let mut rest = coll.into_iter().peekable();
+     let (mut m, mut n) = (self, 0);
+     while let Some((k,v)) = rest.next_if(|kv| p(&m, kv))
+     {m = Map::update(m, k, |opt_u| match opt_u {Some(u) => f(u,v), None => v}); n += 1}
+     (m, n, rest)}
 }
 
 pub trait MapMut<K,V> {
@@ -1,17 +1,25 @@
-use std::collections::{VecDeque,BinaryHeap};
+use transducer::{Transducer,Reducer,SeqInc,transduce};
+use std::collections::{VecDeque,BinaryHeap,LinkedList};
+use std::rc::Rc;
+use std::sync::Arc;
+use std::iter::{once,Peekable};
+use std::{error,fmt};
 
 /// basic protocol for seqs.
 pub trait Seq<T> where Self:Sized {
-    /// a seq maps from indices to items. O(n) for `BinaryHeap`.
+    /// a seq maps from indices to items. O(n) for `BinaryHeap` and
+    /// `LinkedList`.
     fn fun<'a>(&'a self) -> Box<Fn(usize) -> Option<&'a T> + 'a>;
 
-    /// adds item `i`. both `Vec` and `VecDeque` grows to the right.
+    /// adds item `i`. `Vec`, `VecDeque`, `LinkedList` and the slices all grow
+    /// to the right. O(n) for the slices, which are copied into a new one.
     ///
     /// like `clojure`'s [`conj`](http://clojuredocs.org/clojure.core/conj).
     fn inc(self, i:T) -> Self;
 
-    /// removes an item. for `Vec` it's the last one; for `VecDeque` the first;
-    /// for `BinaryHeap` it's the greatest one.
+    /// removes an item. for `Vec` and the slices it's the last one; for
+    /// `VecDeque` and `LinkedList` the first; for `BinaryHeap` it's the greatest
+    /// one.
     ///
     /// like `clojure`'s [`pop`](http://clojuredocs.org/clojure.core/pop) for
     /// vectors and queues.
@@ -23,6 +31,35 @@ pub trait Seq<T> where Self:Sized {
     fn plus<I>(self, coll:I) -> Self where I:IntoIterator<Item = T>
     {coll.into_iter().fold(self, Seq::inc)}
 
+    /// pours `coll` into this one while `p` holds for the seq so far and the
+    /// next item. returns the seq, how many items were poured, and the rest
+    /// of `coll`.
+    ///
+    /// like [`Map::plus_while`](trait.Map.html#method.plus_while).
+    fn plus_while<I,P>(self, coll:I, mut p:P) -> (Self, usize, Peekable<I::IntoIter>)
+        where I:IntoIterator<Item = T>, P:FnMut(&Self, &T) -> bool
+    {let mut rest = coll.into_iter().peekable();
+     let (mut s, mut n) = (self, 0);
+     while let Some(i) = rest.next_if(|i| p(&s, i)) {s = Seq::inc(s, i); n += 1}
+     (s, n, rest)}
+
+    /// pours another collection through the transducer `xform` into this
+    /// one.
+    ///
+    /// like `clojure`'s [`into`](http://clojuredocs.org/clojure.core/into)
+    /// with a transducer.
+    ///
+    /// # example
+    /// ```
+    /// use protocoll::Seq;
+    /// use protocoll::xform::{partition_all,map};
+    /// let v = vec![0].into_with((partition_all(2), map(|c:Vec<i32>| c.iter().sum())), 1..6);
+    /// assert_eq!(v, [0, 3, 7, 5]);
+    /// ```
+    fn into_with<X,I>(self, xform:X, coll:I) -> Self
+        where I:IntoIterator, X:Transducer<SeqInc>, X::Reducer:Reducer<Self,I::Item>
+    {transduce(xform, SeqInc, self, coll)}
+
     /// `clear`.
     fn zero(self) -> Self;
 
@@ -30,6 +67,102 @@ pub trait Seq<T> where Self:Sized {
     fn shrink(self) -> Self;
 }
 
+/// the read-only part of the seq protocol, for seqs of a fixed size.
+///
+/// # example
+/// ```
+/// use protocoll::Array;
+/// let a = [1,2,3];
+/// assert_eq!(a.fun()(2), Some(&3));
+/// assert_eq!(a.fun()(3), None);
+/// ```
+pub trait Array<T> {
+    /// an array maps from indices to items.
+    fn fun<'a>(&'a self) -> Box<dyn Fn(usize) -> Option<&'a T> + 'a>;
+}
+
+/// protocol for seqs which are associative on indices, like `clojure`'s
+/// vectors.
+///
+/// it is apart from [`Seq`](trait.Seq.html) as not every seq has indices to
+/// associate on, e.g. heaps and linked lists, which can only grow at the
+/// ends.
+///
+/// index `len` is always valid for growing the seq: `assoc`, `update`,
+/// `update_mut` and `insert_at` then append to the end. any index beyond that
+/// is an error, as are indices out of bounds for `remove_at` and `swap`. the
+/// [`OutOfBounds`](seq/struct.OutOfBounds.html) error gives the seq back
+/// unchanged.
+///
+/// # example
+/// ```
+/// use protocoll::Vector;
+/// let v = vec![1,2,3]
+///     .assoc(0, 0)
+///     .and_then(|v| v.update(1, |n| n.unwrap_or(0) * 10))
+///     .and_then(|v| v.assoc(3, 4))
+///     .and_then(|v| v.update(4, |n| n.unwrap_or(5)))
+///     .unwrap();
+/// assert_eq!(v, [0,20,3,4,5]);
+/// assert_eq!(v.nth_or(9, &-1), &-1);
+/// let e = v.assoc(9, 9).unwrap_err();
+/// assert_eq!((e.index, e.len, e.seq), (9, 5, vec![0,20,3,4,5]));
+/// ```
+pub trait Vector<T>: Seq<T> {
+    /// replaces the item at `i` with `t`.
+    ///
+    /// like `clojure`'s [`assoc`](http://clojuredocs.org/clojure.core/assoc).
+    fn assoc(self, i:usize, t:T) -> Result<Self,OutOfBounds<Self>>;
+
+    /// updates the item at `i` by `f`, which receives `None` at `len`.
+    ///
+    /// like `clojure`'s [`update`](http://clojuredocs.org/clojure.core/update).
+    fn update<F>(self, i:usize, f:F) -> Result<Self,OutOfBounds<Self>> where F:FnOnce(Option<T>) -> T;
+
+    /// like [`update`](#tymethod.update) but in place. `fnil` is used at `len`.
+    ///
+    /// # example
+    /// ```
+    /// use protocoll::Vector;
+    /// let mut v = vec![1,2];
+    /// v.update_mut(0, 0, |n| *n += 1).unwrap();
+    /// v.update_mut(2, 0, |n| *n += 1).unwrap();
+    /// assert!(v.update_mut(4, 0, |n| *n += 1).is_err());
+    /// assert_eq!(v, [2,2,1]);
+    /// ```
+    fn update_mut<F>(&mut self, i:usize, fnil:T, f:F) -> Result<(),OutOfBounds<()>> where F:FnOnce(&mut T);
+
+    /// the item at `i`, or `default` when out of bounds.
+    ///
+    /// like `clojure`'s [`nth`](http://clojuredocs.org/clojure.core/nth).
+    fn nth_or<'a>(&'a self, i:usize, default:&'a T) -> &'a T
+    {self.fun()(i).unwrap_or(default)}
+
+    /// inserts `t` at `i`, shifting all items after it to the right.
+    fn insert_at(self, i:usize, t:T) -> Result<Self,OutOfBounds<Self>>;
+
+    /// removes the item at `i`, shifting all items after it to the left.
+    fn remove_at(self, i:usize) -> Result<Self,OutOfBounds<Self>>;
+
+    /// swaps the items at `i` and `j`.
+    fn swap(self, i:usize, j:usize) -> Result<Self,OutOfBounds<Self>>;
+}
+
+/// the error of a [`Vector`](../trait.Vector.html) operation at an index out
+/// of bounds, with the seq it consumed, unchanged.
+#[derive(Clone,Copy,PartialEq,Eq,Debug)]
+pub struct OutOfBounds<S> {pub index:usize, pub len:usize, pub seq:S}
+
+impl<S> fmt::Display for OutOfBounds<S>
+{fn fmt(&self, f:&mut fmt::Formatter) -> fmt::Result
+ {write!(f, "index {} out of bounds for length {}", self.index, self.len)}}
+
+impl<S> error::Error for OutOfBounds<S> where S:fmt::Debug {}
+
+/// `Ok(seq)` if `i` is below `bound`.
+fn check_index<S>(seq:S, i:usize, len:usize, bound:usize) -> Result<S,OutOfBounds<S>>
+{if i < bound {Ok(seq)} else {Err(OutOfBounds{index:i, len, seq})}}
+
 impl<T> Seq<T> for Vec<T> {
     fn fun<'a>(&'a self) -> Box<Fn(usize) -> Option<&'a T> + 'a>
     {Box::new(move |i| self.get(i))}
@@ -80,3 +213,171 @@ impl<T> Seq<T> for BinaryHeap<T> where T:Ord {
     fn shrink(mut self) -> Self
     {self.shrink_to_fit(); self}
 }
+
+impl<T> Seq<T> for LinkedList<T> {
+    fn fun<'a>(&'a self) -> Box<dyn Fn(usize) -> Option<&'a T> + 'a>
+    {Box::new(move |i| self.iter().nth(i))}
+
+    fn inc(mut self, i:T) -> Self
+    {self.push_back(i); self}
+
+    fn dec(mut self) -> Self
+    {self.pop_front(); self}
+
+    fn zero(mut self) -> Self
+    {self.clear(); self}
+
+    fn shrink(self) -> Self
+    {self}
+}
+
+impl<T> Seq<T> for Box<[T]> {
+    fn fun<'a>(&'a self) -> Box<dyn Fn(usize) -> Option<&'a T> + 'a>
+    {Box::new(move |i| self.get(i))}
+
+    fn inc(self, i:T) -> Self
+    {Seq::inc(self.into_vec(), i).into_boxed_slice()}
+
+    fn dec(self) -> Self
+    {Seq::dec(self.into_vec()).into_boxed_slice()}
+
+    /// copies only once.
+    fn plus<I>(self, coll:I) -> Self where I:IntoIterator<Item = T>
+    {Seq::plus(self.into_vec(), coll).into_boxed_slice()}
+
+    fn zero(self) -> Self
+    {Vec::new().into_boxed_slice()}
+
+    fn shrink(self) -> Self
+    {self}
+}
+
+impl<T> Seq<T> for Rc<[T]> where T:Clone {
+    fn fun<'a>(&'a self) -> Box<dyn Fn(usize) -> Option<&'a T> + 'a>
+    {Box::new(move |i| self.get(i))}
+
+    fn inc(self, i:T) -> Self
+    {self.iter().cloned().chain(once(i)).collect()}
+
+    fn dec(self) -> Self
+    {let l = self.len().saturating_sub(1); self[..l].into()}
+
+    /// copies only once.
+    fn plus<I>(self, coll:I) -> Self where I:IntoIterator<Item = T>
+    {self.iter().cloned().chain(coll).collect()}
+
+    fn zero(self) -> Self
+    {Vec::new().into()}
+
+    fn shrink(self) -> Self
+    {self}
+}
+
+impl<T> Seq<T> for Arc<[T]> where T:Clone {
+    fn fun<'a>(&'a self) -> Box<dyn Fn(usize) -> Option<&'a T> + 'a>
+    {Box::new(move |i| self.get(i))}
+
+    fn inc(self, i:T) -> Self
+    {self.iter().cloned().chain(once(i)).collect()}
+
+    fn dec(self) -> Self
+    {let l = self.len().saturating_sub(1); self[..l].into()}
+
+    /// copies only once.
+    fn plus<I>(self, coll:I) -> Self where I:IntoIterator<Item = T>
+    {self.iter().cloned().chain(coll).collect()}
+
+    fn zero(self) -> Self
+    {Vec::new().into()}
+
+    fn shrink(self) -> Self
+    {self}
+}
+
+impl<T, const N:usize> Array<T> for [T;N] {
+    fn fun<'a>(&'a self) -> Box<dyn Fn(usize) -> Option<&'a T> + 'a>
+    {Box::new(move |i| self.get(i))}
+}
+
+impl<T> Vector<T> for Vec<T> {
+    fn assoc(self, i:usize, t:T) -> Result<Self,OutOfBounds<Self>>
+    {let l = self.len();
+     let mut v = check_index(self, i, l, l + 1)?;
+     if i == l {v.push(t)} else {v[i] = t}
+     Ok(v)}
+
+    fn update<F>(self, i:usize, f:F) -> Result<Self,OutOfBounds<Self>> where F:FnOnce(Option<T>) -> T
+    {let l = self.len();
+     let mut v = check_index(self, i, l, l + 1)?;
+     if i == l {v.push(f(None))}
+     else {let t = v.swap_remove(i);
+           v.push(f(Some(t)));
+           v.as_mut_slice().swap(i, l - 1)}
+     Ok(v)}
+
+    fn update_mut<F>(&mut self, i:usize, fnil:T, f:F) -> Result<(),OutOfBounds<()>> where F:FnOnce(&mut T)
+    {let l = self.len();
+     check_index((), i, l, l + 1)?;
+     if i == l {self.push(fnil)}
+     f(&mut self[i]);
+     Ok(())}
+
+    fn insert_at(self, i:usize, t:T) -> Result<Self,OutOfBounds<Self>>
+    {let l = self.len();
+     let mut v = check_index(self, i, l, l + 1)?;
+     v.insert(i, t);
+     Ok(v)}
+
+    fn remove_at(self, i:usize) -> Result<Self,OutOfBounds<Self>>
+    {let l = self.len();
+     let mut v = check_index(self, i, l, l)?;
+     v.remove(i);
+     Ok(v)}
+
+    fn swap(self, i:usize, j:usize) -> Result<Self,OutOfBounds<Self>>
+    {let l = self.len();
+     let mut v = check_index(self, i.max(j), l, l)?;
+     v.as_mut_slice().swap(i, j);
+     Ok(v)}
+}
+
+impl<T> Vector<T> for VecDeque<T> {
+    fn assoc(self, i:usize, t:T) -> Result<Self,OutOfBounds<Self>>
+    {let l = self.len();
+     let mut v = check_index(self, i, l, l + 1)?;
+     if i == l {v.push_back(t)} else {v[i] = t}
+     Ok(v)}
+
+    fn update<F>(self, i:usize, f:F) -> Result<Self,OutOfBounds<Self>> where F:FnOnce(Option<T>) -> T
+    {let l = self.len();
+     let mut v = check_index(self, i, l, l + 1)?;
+     match v.swap_remove_back(i)
+     {None => v.push_back(f(None)),
+      Some(t) => {v.push_back(f(Some(t))); VecDeque::swap(&mut v, i, l - 1)}}
+     Ok(v)}
+
+    fn update_mut<F>(&mut self, i:usize, fnil:T, f:F) -> Result<(),OutOfBounds<()>> where F:FnOnce(&mut T)
+    {let l = self.len();
+     check_index((), i, l, l + 1)?;
+     if i == l {self.push_back(fnil)}
+     f(&mut self[i]);
+     Ok(())}
+
+    fn insert_at(self, i:usize, t:T) -> Result<Self,OutOfBounds<Self>>
+    {let l = self.len();
+     let mut v = check_index(self, i, l, l + 1)?;
+     v.insert(i, t);
+     Ok(v)}
+
+    fn remove_at(self, i:usize) -> Result<Self,OutOfBounds<Self>>
+    {let l = self.len();
+     let mut v = check_index(self, i, l, l)?;
+     v.remove(i);
+     Ok(v)}
+
+    fn swap(self, i:usize, j:usize) -> Result<Self,OutOfBounds<Self>>
+    {let l = self.len();
+     let mut v = check_index(self, i.max(j), l, l)?;
+     VecDeque::swap(&mut v, i, j);
+     Ok(v)}
+}
@@ -1,6 +1,8 @@
+use transducer::{Transducer,Reducer,SetInc,transduce};
 use std::collections::{HashSet,BTreeSet};
 use std::borrow::Borrow;
 use std::hash::Hash;
+use std::iter::Peekable;
 
 /// basic protocol for sets.
 pub trait Set<T> where Self:Sized {
@@ -23,6 +25,27 @@ pub trait Set<T> where Self:Sized {
     fn plus<I>(self, coll:I) -> Self where I:IntoIterator<Item = T>
     {coll.into_iter().fold(self, Set::inc)}
 
+    /// pours `coll` into this one while `p` holds for the set so far and the
+    /// next item. returns the set, how many items were poured, and the rest
+    /// of `coll`.
+    ///
+    /// like [`Map::plus_while`](trait.Map.html#method.plus_while).
+    fn plus_while<I,P>(self, coll:I, mut p:P) -> (Self, usize, Peekable<I::IntoIter>)
+        where I:IntoIterator<Item = T>, P:FnMut(&Self, &T) -> bool
+    {let mut rest = coll.into_iter().peekable();
+     let (mut s, mut n) = (self, 0);
+     while let Some(i) = rest.next_if(|i| p(&s, i)) {s = Set::inc(s, i); n += 1}
+     (s, n, rest)}
+
+    /// pours another collection through the transducer `xform` into this
+    /// one.
+    ///
+    /// like `clojure`'s [`into`](http://clojuredocs.org/clojure.core/into)
+    /// with a transducer.
+    fn into_with<X,I>(self, xform:X, coll:I) -> Self
+        where I:IntoIterator, X:Transducer<SetInc>, X::Reducer:Reducer<Self,I::Item>
+    {transduce(xform, SetInc, self, coll)}
+
     /// `clear`.
     fn zero(self) -> Self;
 
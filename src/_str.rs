@@ -1,9 +1,31 @@
+use std::borrow::Cow;
+use std::ffi::OsString;
+use std::path::PathBuf;
+
 /// basic protocol for strs;
+///
+/// a str is made of units: `char`s for `String`, `Cow<str>` and `OsString`;
+/// components for `PathBuf`; bytes for `Vec<u8>`. `inc` always appends a
+/// char and `plus` a str, while `dec` pops the last unit. units which are not
+/// chars are appended by [`StrUnits::inc_unit`](trait.StrUnits.html).
+///
+/// `Vec<u8>` is also a [`Seq`](trait.Seq.html), so when both traits are in
+/// scope, the functions have to be called as `Str::plus(v, s)` and so on.
+///
+/// # example
+/// ```
+/// use protocoll::{Str,StrUnits};
+/// use std::path::PathBuf;
+/// let p = PathBuf::from("/usr").plus("lib/rust").dec().inc_unit("bin".into());
+/// assert_eq!(p, PathBuf::from("/usr/lib/bin"));
+/// let b = Str::plus(b"caf".to_vec(), "\u{e9}").inc('!').inc_unit(b'?');
+/// assert_eq!(b, "caf\u{e9}!?".as_bytes());
+/// ```
 pub trait Str {
     /// appends char `c`.
     fn inc(self, c:char) -> Self;
 
-    /// pops the last char.
+    /// pops the last unit.
     fn dec(self) -> Self;
 
     /// appends str `s`.
@@ -16,6 +38,16 @@ pub trait Str {
     fn shrink(self) -> Self;
 }
 
+/// strs whose units, which [`Str::dec`](trait.Str.html#tymethod.dec) pops,
+/// can be appended one at a time.
+pub trait StrUnits: Str {
+    /// the unit of this str.
+    type Unit;
+
+    /// appends unit `u`.
+    fn inc_unit(self, u:Self::Unit) -> Self;
+}
+
 impl Str for String {
     fn inc(mut self, c:char) -> Self
     {self.push(c); self}
@@ -32,3 +64,149 @@ impl Str for String {
     fn shrink(mut self) -> Self
     {self.shrink_to_fit(); self}
 }
+
+/// a borrowed str is only copied when growing; `dec` and `zero` keep it
+/// borrowed.
+impl<'a> Str for Cow<'a,str> {
+    fn inc(mut self, c:char) -> Self
+    {self.to_mut().push(c); self}
+
+    fn dec(self) -> Self
+    {match self
+     {Cow::Borrowed(s) => {let mut cs = s.chars(); cs.next_back(); Cow::Borrowed(cs.as_str())}
+      Cow::Owned(s) => Cow::Owned(Str::dec(s))}}
+
+    fn plus(mut self, s:&str) -> Self
+    {self.to_mut().push_str(s); self}
+
+    fn zero(self) -> Self
+    {match self
+     {Cow::Borrowed(_) => Cow::Borrowed(""),
+      Cow::Owned(s) => Cow::Owned(Str::zero(s))}}
+
+    fn shrink(self) -> Self
+    {match self
+     {Cow::Borrowed(s) => Cow::Borrowed(s),
+      Cow::Owned(s) => Cow::Owned(Str::shrink(s))}}
+}
+
+/// `dec` pops the last char. when the os string is not valid unicode, a lone
+/// byte (on unix) or a lone code unit (on windows) which is not part of any
+/// char counts as a char of its own.
+impl Str for OsString {
+    fn inc(mut self, c:char) -> Self
+    {self.push(c.encode_utf8(&mut [0;4])); self}
+
+    #[cfg(unix)]
+    fn dec(self) -> Self
+    {use std::os::unix::ffi::OsStringExt;
+     let mut v = self.into_vec();
+     let l = v.len();
+     let n = (1..l.min(4) + 1)
+         .find(|&n| ::std::str::from_utf8(&v[l-n..]).is_ok())
+         .unwrap_or(l.min(1));
+     v.truncate(l - n);
+     OsString::from_vec(v)}
+
+    #[cfg(windows)]
+    fn dec(self) -> Self
+    {use std::os::windows::ffi::{OsStrExt,OsStringExt};
+     let mut v:Vec<u16> = self.encode_wide().collect();
+     match v.pop()
+     {Some(lo) if 0xDC00 <= lo && lo < 0xE000 =>
+      {match v.last() {Some(&hi) if 0xD800 <= hi && hi < 0xDC00 => {v.pop();} _ => ()}}
+      _ => ()}
+     OsString::from_wide(&v)}
+
+    #[cfg(not(any(unix,windows)))]
+    fn dec(self) -> Self
+    {match self.into_string()
+     {Ok(s) => Str::dec(s).into(),
+      Err(s) => s}}
+
+    fn plus(mut self, s:&str) -> Self
+    {self.push(s); self}
+
+    fn zero(mut self) -> Self
+    {self.clear(); self}
+
+    fn shrink(mut self) -> Self
+    {self.shrink_to_fit(); self}
+}
+
+/// the units are path components: `inc` and `plus` push a char or a str as
+/// a path, `dec` pops the last component.
+impl Str for PathBuf {
+    fn inc(mut self, c:char) -> Self
+    {self.push(c.encode_utf8(&mut [0;4])); self}
+
+    fn dec(mut self) -> Self
+    {self.pop(); self}
+
+    fn plus(mut self, s:&str) -> Self
+    {self.push(s); self}
+
+    fn zero(mut self) -> Self
+    {self.clear(); self}
+
+    fn shrink(mut self) -> Self
+    {self.shrink_to_fit(); self}
+}
+
+/// a byte string. `inc` and `plus` append the utf-8 encoding of `c` and `s`.
+///
+/// since `Vec<u8>` is also a [`Seq`](trait.Seq.html), the protocol functions
+/// have to be called as `Str::plus` and so on when both traits are in scope.
+impl Str for Vec<u8> {
+    fn inc(mut self, c:char) -> Self
+    {self.extend_from_slice(c.encode_utf8(&mut [0;4]).as_bytes()); self}
+
+    fn dec(mut self) -> Self
+    {self.pop(); self}
+
+    fn plus(mut self, s:&str) -> Self
+    {self.extend_from_slice(s.as_bytes()); self}
+
+    fn zero(mut self) -> Self
+    {self.clear(); self}
+
+    fn shrink(mut self) -> Self
+    {self.shrink_to_fit(); self}
+}
+
+impl StrUnits for String {
+    type Unit = char;
+
+    fn inc_unit(self, u:char) -> Self
+    {Str::inc(self, u)}
+}
+
+impl<'a> StrUnits for Cow<'a,str> {
+    type Unit = char;
+
+    fn inc_unit(self, u:char) -> Self
+    {Str::inc(self, u)}
+}
+
+impl StrUnits for OsString {
+    type Unit = char;
+
+    fn inc_unit(self, u:char) -> Self
+    {Str::inc(self, u)}
+}
+
+/// pushes a component.
+impl StrUnits for PathBuf {
+    type Unit = OsString;
+
+    fn inc_unit(mut self, u:OsString) -> Self
+    {self.push(u); self}
+}
+
+/// appends a byte.
+impl StrUnits for Vec<u8> {
+    type Unit = u8;
+
+    fn inc_unit(mut self, u:u8) -> Self
+    {self.push(u); self}
+}
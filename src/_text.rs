@@ -0,0 +1,317 @@
+use _str::Str;
+use _map::Map;
+use grapheme::{graphemes,byte_index};
+use std::fmt::{Display,Write};
+
+/// what to look for in a str: a `char`, a str or a predicate on chars.
+pub trait Pattern {
+    /// the byte range of the first match in `s` starting at or after `from`,
+    /// which is always on a char boundary.
+    fn find_in(&mut self, s:&str, from:usize) -> Option<(usize,usize)>;
+}
+
+impl Pattern for char
+{fn find_in(&mut self, s:&str, from:usize) -> Option<(usize,usize)>
+ {s[from..].find(*self).map(|i| (from + i, from + i + self.len_utf8()))}}
+
+impl Pattern for &str
+{fn find_in(&mut self, s:&str, from:usize) -> Option<(usize,usize)>
+ {s[from..].find(*self).map(|i| (from + i, from + i + self.len()))}}
+
+impl Pattern for &String
+{fn find_in(&mut self, s:&str, from:usize) -> Option<(usize,usize)>
+ {self.as_str().find_in(s, from)}}
+
+impl<F> Pattern for F where F:FnMut(char) -> bool
+{fn find_in(&mut self, s:&str, from:usize) -> Option<(usize,usize)>
+ {s[from..].char_indices().find(|&(_,c)| self(c)).map(|(i,c)| (from + i, from + i + c.len_utf8()))}}
+
+/// what to replace a match with: a str, or a function from the matched str.
+pub trait Replacement {
+    fn replace_into(&mut self, matched:&str, out:&mut String);
+}
+
+impl Replacement for &str
+{fn replace_into(&mut self, _:&str, out:&mut String) {out.push_str(self)}}
+
+impl Replacement for char
+{fn replace_into(&mut self, _:&str, out:&mut String) {out.push(*self)}}
+
+impl<F,S> Replacement for F where F:FnMut(&str) -> S, S:AsRef<str>
+{fn replace_into(&mut self, matched:&str, out:&mut String) {out.push_str(self(matched).as_ref())}}
+
+/// the byte index of the char boundary after `i`.
+fn next_boundary(s:&str, i:usize) -> usize
+{i + s[i..].chars().next().map_or(1, char::len_utf8)}
+
+/// splits `s` at the matches of `p` into at most `n` parts. a match of zero
+/// width splits between chars, but never at either end of `s`.
+fn split_n<P>(s:&str, mut p:P, n:usize) -> Vec<&str> where P:Pattern {
+    let mut parts = Vec::new();
+    let (mut last, mut from) = (0,0);
+    while parts.len() + 1 < n && from <= s.len() {
+        match p.find_in(s, from) {
+            None => break,
+            Some((a,b)) if a == b => {
+                if a == s.len() {break}
+                if a > 0 {parts.push(&s[last..a]); last = a}
+                from = next_boundary(s, a);
+            }
+            Some((a,b)) => {parts.push(&s[last..a]); last = b; from = b}
+        }
+    }
+    parts.push(&s[last..]); parts
+}
+
+/// replaces the matches of `p` in `s`, at most `n` of them.
+fn replace_n<P,R>(s:&str, mut p:P, mut r:R, n:usize) -> String where P:Pattern, R:Replacement {
+    let mut out = String::with_capacity(s.len());
+    let (mut last, mut from, mut count) = (0,0,0);
+    while count < n && from <= s.len() {
+        match p.find_in(s, from) {
+            None => break,
+            Some((a,b)) => {
+                out.push_str(&s[last..a]);
+                r.replace_into(&s[a..b], &mut out);
+                last = b; count += 1;
+                from = if a == b {if b == s.len() {break} next_boundary(s, b)} else {b};
+                if a == b {out.push_str(&s[b..from]); last = from}
+            }
+        }
+    }
+    out.push_str(&s[last..]); out
+}
+
+/// the [`clojure.string`](https://clojure.github.io/clojure/clojure.string-api.html)
+/// vocabulary for [`Str`](trait.Str.html)s which are views of a single str.
+///
+/// everything consumes `self` and produces a new one, except for the
+/// predicates. where `clojure`'s name is taken by a method of `str`, which
+/// this would shadow on a `String` with a call that moves it, the method has
+/// another name: `split_on`, `replace_all`, `trim_both`, `has_prefix` and
+/// `has_suffix`.
+///
+/// # example
+/// ```
+/// use protocoll::Text;
+/// let s = String::join(", ", &[1,2,3]);
+/// assert_eq!(s, "1, 2, 3");
+/// let v = String::from("a-b_c").split_on(|c| c == '-' || c == '_');
+/// assert_eq!(v, ["a","b","c"]);
+/// let s = String::from("  hello world\n").trim_both().capitalize();
+/// assert_eq!(s, "Hello world");
+/// let s = String::from(" str methods stay ");
+/// assert_eq!(s.trim(), "str methods stay");
+/// assert!(s.starts_with(' ') && s.has_suffix(" "));
+/// ```
+pub trait Text: Str + AsRef<str> + From<String> where Self:Sized {
+    /// the items of `coll` separated by `sep`.
+    ///
+    /// like `clojure`'s [`join`](http://clojuredocs.org/clojure.string/join).
+    fn join<I>(sep:&str, coll:I) -> Self where I:IntoIterator, I::Item:Display
+    {let mut out = String::new();
+     for (n,i) in coll.into_iter().enumerate()
+     {if n > 0 {out.push_str(sep)}
+      write!(out, "{}", i).expect("a Display implementation returned an error unexpectedly")}
+     Self::from(out)}
+
+    /// splits at every match of `p`, dropping trailing empty parts.
+    ///
+    /// like `clojure`'s [`split`](http://clojuredocs.org/clojure.string/split).
+    fn split_on<P>(self, p:P) -> Vec<Self> where P:Pattern
+    {let mut parts = split_n(self.as_ref(), p, usize::MAX);
+     while parts.len() > 1 && parts.last().is_some_and(|s| s.is_empty()) {parts.pop();}
+     parts.into_iter().map(|s| Self::from(s.to_owned())).collect()}
+
+    /// splits into at most `n` parts, keeping trailing empty parts. the last
+    /// part holds the rest of the str.
+    ///
+    /// # example
+    /// ```
+    /// use protocoll::Text;
+    /// assert_eq!(String::from("a,b,,").split_on(','), ["a","b"]);
+    /// assert_eq!(String::from("a,b,,").split_on_n(',', 9), ["a","b","",""]);
+    /// assert_eq!(String::from("a,b,,").split_on_n(',', 2), ["a","b,,"]);
+    /// ```
+    fn split_on_n<P>(self, p:P, n:usize) -> Vec<Self> where P:Pattern
+    {split_n(self.as_ref(), p, n).into_iter().map(|s| Self::from(s.to_owned())).collect()}
+
+    /// splits at `\n` or `\r\n`.
+    ///
+    /// like `clojure`'s [`split-lines`](http://clojuredocs.org/clojure.string/split-lines).
+    fn split_lines(self) -> Vec<Self>
+    {self.as_ref().lines().map(|s| Self::from(s.to_owned())).collect()}
+
+    /// replaces all matches of `p` by `r`.
+    ///
+    /// like `clojure`'s [`replace`](http://clojuredocs.org/clojure.string/replace).
+    ///
+    /// # example
+    /// ```
+    /// use protocoll::Text;
+    /// let s = String::from("one two three");
+    /// assert_eq!(s.clone().replace_all(' ', "_"), "one_two_three");
+    /// assert_eq!(s.replace_all(char::is_whitespace, |m:&str| format!("[{}]", m.len())),
+    ///            "one[1]two[1]three");
+    /// ```
+    fn replace_all<P,R>(self, p:P, r:R) -> Self where P:Pattern, R:Replacement
+    {Self::from(replace_n(self.as_ref(), p, r, usize::MAX))}
+
+    /// replaces the first match of `p` by `r`.
+    ///
+    /// like `clojure`'s [`replace-first`](http://clojuredocs.org/clojure.string/replace-first).
+    fn replace_first<P,R>(self, p:P, r:R) -> Self where P:Pattern, R:Replacement
+    {Self::from(replace_n(self.as_ref(), p, r, 1))}
+
+    /// removes whitespace from both ends.
+    ///
+    /// like `clojure`'s [`trim`](http://clojuredocs.org/clojure.string/trim).
+    fn trim_both(self) -> Self
+    {Self::from(self.as_ref().trim().to_owned())}
+
+    /// removes whitespace from the left.
+    ///
+    /// like `clojure`'s [`triml`](http://clojuredocs.org/clojure.string/triml).
+    fn triml(self) -> Self
+    {Self::from(self.as_ref().trim_start().to_owned())}
+
+    /// removes whitespace from the right.
+    ///
+    /// like `clojure`'s [`trimr`](http://clojuredocs.org/clojure.string/trimr).
+    fn trimr(self) -> Self
+    {Self::from(self.as_ref().trim_end().to_owned())}
+
+    /// removes all trailing `\n` and `\r`.
+    ///
+    /// like `clojure`'s [`trim-newline`](http://clojuredocs.org/clojure.string/trim-newline).
+    fn trim_newline(self) -> Self
+    {Self::from(self.as_ref().trim_end_matches(['\n','\r']).to_owned())}
+
+    /// the first char in upper case and the rest in lower case.
+    ///
+    /// like `clojure`'s [`capitalize`](http://clojuredocs.org/clojure.string/capitalize).
+    fn capitalize(self) -> Self
+    {let mut cs = self.as_ref().chars();
+     let mut out = String::with_capacity(self.as_ref().len());
+     if let Some(c) = cs.next() {out.extend(c.to_uppercase())}
+     for c in cs {out.extend(c.to_lowercase())}
+     Self::from(out)}
+
+    /// like `clojure`'s [`upper-case`](http://clojuredocs.org/clojure.string/upper-case).
+    fn upper(self) -> Self
+    {Self::from(self.as_ref().to_uppercase())}
+
+    /// like `clojure`'s [`lower-case`](http://clojuredocs.org/clojure.string/lower-case).
+    fn lower(self) -> Self
+    {Self::from(self.as_ref().to_lowercase())}
+
+    /// the chars in reverse order.
+    ///
+    /// like `clojure`'s [`reverse`](http://clojuredocs.org/clojure.string/reverse).
+    fn reverse(self) -> Self
+    {Self::from(self.as_ref().chars().rev().collect::<String>())}
+
+    /// replaces every char which is a key in `cmap` by its value.
+    ///
+    /// like `clojure`'s [`escape`](http://clojuredocs.org/clojure.string/escape).
+    ///
+    /// # example
+    /// ```
+    /// use protocoll::Text;
+    /// use std::collections::HashMap;
+    /// let cmap:HashMap<_,_> = vec![('<',"&lt;"),('>',"&gt;")].into_iter().collect();
+    /// assert_eq!(String::from("<a>").escape(&cmap), "&lt;a&gt;");
+    /// ```
+    fn escape<M,S>(self, cmap:&M) -> Self where M:Map<char,S>, S:AsRef<str>
+    {let f = cmap.fun();
+     let mut out = String::with_capacity(self.as_ref().len());
+     for c in self.as_ref().chars()
+     {match f(&c) {Some(s) => out.push_str(s.as_ref()), None => out.push(c)}}
+     Self::from(out)}
+
+    /// pops the last extended grapheme cluster, which may be made of several
+    /// chars. unlike [`Str::dec`](trait.Str.html#tymethod.dec), this never
+    /// splits an emoji or a letter from its combining marks.
+    ///
+    /// # example
+    /// ```
+    /// use protocoll::{Str,Text};
+    /// let s = String::from("cafe\u{301}");
+    /// assert_eq!(s.clone().dec(), "cafe");
+    /// assert_eq!(s.dec_grapheme(), "caf");
+    /// ```
+    fn dec_grapheme(self) -> Self
+    {let s = self.as_ref();
+     let l = s.len() - graphemes(s).next_back().map_or(0, str::len);
+     Self::from(s[..l].to_owned())}
+
+    /// the number of extended grapheme clusters.
+    fn count_graphemes(&self) -> usize
+    {graphemes(self.as_ref()).count()}
+
+    /// the extended grapheme clusters from index `from` up to but excluding
+    /// `to`. panics if `from > to` or `to` is out of bounds.
+    ///
+    /// like `clojure`'s [`subs`](http://clojuredocs.org/clojure.core/subs) but
+    /// indexed by grapheme clusters.
+    ///
+    /// # example
+    /// ```
+    /// use protocoll::Text;
+    /// let s = String::from("\u{1F44D}\u{1F3FD} ok");
+    /// assert_eq!(s.count_graphemes(), 4);
+    /// assert_eq!(s.clone().slice_graphemes(0, 1), "\u{1F44D}\u{1F3FD}");
+    /// assert_eq!(s.truncate_graphemes(2), "\u{1F44D}\u{1F3FD} ");
+    /// ```
+    fn slice_graphemes(self, from:usize, to:usize) -> Self
+    {let s = self.as_ref();
+     assert!(from <= to, "slice index starts at {} but ends at {}", from, to);
+     let i = byte_index(s, from);
+     let j = i.and_then(|i| byte_index(&s[i..], to - from).map(|j| i + j));
+     match (i,j)
+     {(Some(i),Some(j)) => Self::from(s[i..j].to_owned()),
+      _ => panic!("grapheme index {} out of bounds", to)}}
+
+    /// keeps only the first `n` extended grapheme clusters.
+    fn truncate_graphemes(self, n:usize) -> Self
+    {let s = self.as_ref();
+     match byte_index(s, n)
+     {Some(l) => Self::from(s[..l].to_owned()),
+      None => self}}
+
+    /// empty or only whitespace.
+    ///
+    /// like `clojure`'s [`blank?`](http://clojuredocs.org/clojure.string/blank_q).
+    fn is_blank(&self) -> bool
+    {self.as_ref().chars().all(char::is_whitespace)}
+
+    /// like `clojure`'s [`starts-with?`](http://clojuredocs.org/clojure.string/starts-with_q).
+    fn has_prefix(&self, s:&str) -> bool
+    {self.as_ref().starts_with(s)}
+
+    /// like `clojure`'s [`ends-with?`](http://clojuredocs.org/clojure.string/ends-with_q).
+    fn has_suffix(&self, s:&str) -> bool
+    {self.as_ref().ends_with(s)}
+
+    /// like `clojure`'s [`includes?`](http://clojuredocs.org/clojure.string/includes_q).
+    fn includes(&self, s:&str) -> bool
+    {self.as_ref().contains(s)}
+}
+
+/// trimming the right end and dropping grapheme clusters happen in place.
+impl Text for String {
+    fn trimr(mut self) -> Self
+    {let l = self.trim_end().len(); self.truncate(l); self}
+
+    fn trim_newline(mut self) -> Self
+    {let l = self.trim_end_matches(['\n','\r']).len(); self.truncate(l); self}
+
+    fn dec_grapheme(mut self) -> Self
+    {let l = self.len() - graphemes(&self).next_back().map_or(0, str::len);
+     self.truncate(l); self}
+
+    fn truncate_graphemes(mut self, n:usize) -> Self
+    {if let Some(l) = byte_index(&self, n) {self.truncate(l)} self}
+}
+
+impl<'a> Text for ::std::borrow::Cow<'a,str> {}
@@ -0,0 +1,222 @@
+use value::Value;
+use json_value::Json;
+use vec_sorted_map::VecSortedMap;
+use _map::Map;
+use std::collections::{HashMap,BTreeMap,HashSet,BTreeSet,VecDeque};
+use std::hash::Hash;
+
+/// what [`deep_merge`](trait.DeepMerge.html#method.deep_merge) does with two
+/// sequences.
+#[derive(Clone,Copy,PartialEq,Eq,Debug,Default)]
+pub enum SeqPolicy {
+    /// the new one replaces the old one.
+    #[default]
+    Replace,
+    /// the new items follow the old ones.
+    Concat,
+    /// the new items which are not among the old ones follow them. this
+    /// compares every new item with every old one, so it takes O(n·m) time.
+    Union,
+}
+
+/// what [`deep_merge`](trait.DeepMerge.html#method.deep_merge) does with a new
+/// value which is `None` or nil.
+#[derive(Clone,Copy,PartialEq,Eq,Debug,Default)]
+pub enum NilPolicy {
+    /// it is kept as a value like any other.
+    #[default]
+    Keep,
+    /// it deletes the entry from the map.
+    Delete,
+    /// it is ignored, so the old value stays.
+    Ignore,
+}
+
+/// the policies for [`deep_merge`](trait.DeepMerge.html#method.deep_merge).
+/// the default replaces sequences and keeps nils, which is what `clojure`'s
+/// `merge` does at each level.
+#[derive(Clone,Copy,PartialEq,Eq,Debug,Default)]
+pub struct MergePolicy {pub seqs:SeqPolicy, pub nils:NilPolicy}
+
+/// values which merge recursively: maps merge entry by entry, sequences and
+/// sets as the [`MergePolicy`](struct.MergePolicy.html) says, and anything
+/// else is a leaf.
+///
+/// # example
+/// ```
+/// use protocoll::map::{DeepMerge,MergePolicy,SeqPolicy,NilPolicy};
+/// use protocoll::json::{self,Json};
+/// let defaults:Json = json::from_str(r#"{"db": {"host": "localhost", "port": 5432}, "tags": ["a"]}"#).unwrap();
+/// let env:Json = json::from_str(r#"{"db": {"host": "db.internal", "user": null}, "tags": ["b", "a"]}"#).unwrap();
+/// let p = MergePolicy{seqs:SeqPolicy::Union, nils:NilPolicy::Delete};
+/// assert_eq!(defaults.clone().deep_merge(env.clone(), &p).to_string(),
+///            r#"{"db":{"host":"db.internal","port":5432},"tags":["a","b"]}"#);
+/// let sum = defaults.deep_merge_with(env, &MergePolicy::default(),
+///                                    &mut |a, b| if a.is_null() {b} else {a});
+/// assert_eq!(sum.to_string(), r#"{"db":{"host":"localhost","port":5432,"user":null},"tags":["b","a"]}"#);
+/// ```
+pub trait DeepMerge where Self:Sized {
+    /// the type of the leaves.
+    type Leaf;
+
+    /// merges `other` into this one, resolving conflicting leaves by
+    /// `f(old, new)`.
+    ///
+    /// like `deep-merge-with` from `clojure.contrib`.
+    fn deep_merge_with<F>(self, other:Self, p:&MergePolicy, f:&mut F) -> Self
+        where F:FnMut(Self::Leaf, Self::Leaf) -> Self::Leaf;
+
+    /// merges `other` into this one, where its leaves win.
+    fn deep_merge(self, other:Self, p:&MergePolicy) -> Self
+    {self.deep_merge_with(other, p, &mut |_, new| new)}
+
+    /// whether this is `None` or nil, for the
+    /// [`NilPolicy`](enum.NilPolicy.html).
+    fn is_nil(&self) -> bool
+    {false}
+}
+
+macro_rules! leaves {
+    ($($t:ty)*) => {$(
+        impl DeepMerge for $t
+        {type Leaf = $t;
+         fn deep_merge_with<F>(self, other:$t, _:&MergePolicy, f:&mut F) -> $t where F:FnMut($t,$t) -> $t
+         {f(self, other)}}
+    )*}
+}
+
+leaves!(bool char i8 i16 i32 i64 isize u8 u16 u32 u64 usize f32 f64 String);
+
+/// `None` is nil. with [`NilPolicy::Ignore`](enum.NilPolicy.html#variant.Ignore)
+/// a new `None` keeps the old value; otherwise it replaces it.
+impl<T> DeepMerge for Option<T> where T:DeepMerge {
+    type Leaf = T::Leaf;
+
+    fn deep_merge_with<F>(self, other:Self, p:&MergePolicy, f:&mut F) -> Self where F:FnMut(T::Leaf, T::Leaf) -> T::Leaf
+    {match (self, other)
+     {(Some(a), Some(b)) => Some(a.deep_merge_with(b, p, f)),
+      (a, None) => if p.nils == NilPolicy::Ignore {a} else {None},
+      (None, b) => b}}
+
+    fn is_nil(&self) -> bool
+    {self.is_none()}
+}
+
+/// merges by the [`SeqPolicy`](enum.SeqPolicy.html).
+fn merge_seq<T,S>(mut a:S, b:S, p:&MergePolicy) -> S where T:PartialEq, S:Extend<T> + IntoIterator<Item = T>, for<'a> &'a S:IntoIterator<Item = &'a T>
+{match p.seqs
+ {SeqPolicy::Replace => b,
+  SeqPolicy::Concat => {a.extend(b); a}
+  SeqPolicy::Union =>
+  {let mut new = Vec::new();
+   for t in b {if !(&a).into_iter().any(|u| u == &t) && !new.contains(&t) {new.push(t)}}
+   a.extend(new); a}}}
+
+/// merges by the [`SeqPolicy`](enum.SeqPolicy.html), whose leaves are not
+/// merged.
+impl<T> DeepMerge for Vec<T> where T:DeepMerge + PartialEq {
+    type Leaf = T::Leaf;
+
+    fn deep_merge_with<F>(self, other:Self, p:&MergePolicy, _:&mut F) -> Self where F:FnMut(T::Leaf, T::Leaf) -> T::Leaf
+    {merge_seq(self, other, p)}
+}
+
+/// merges by the [`SeqPolicy`](enum.SeqPolicy.html), whose leaves are not
+/// merged.
+impl<T> DeepMerge for VecDeque<T> where T:DeepMerge + PartialEq {
+    type Leaf = T::Leaf;
+
+    fn deep_merge_with<F>(self, other:Self, p:&MergePolicy, _:&mut F) -> Self where F:FnMut(T::Leaf, T::Leaf) -> T::Leaf
+    {merge_seq(self, other, p)}
+}
+
+/// the union, unless the [`SeqPolicy`](enum.SeqPolicy.html) is to replace.
+impl<T> DeepMerge for HashSet<T> where T:DeepMerge + Hash + Eq {
+    type Leaf = T::Leaf;
+
+    fn deep_merge_with<F>(mut self, other:Self, p:&MergePolicy, _:&mut F) -> Self where F:FnMut(T::Leaf, T::Leaf) -> T::Leaf
+    {if p.seqs == SeqPolicy::Replace {other} else {self.extend(other); self}}
+}
+
+/// the union, unless the [`SeqPolicy`](enum.SeqPolicy.html) is to replace.
+impl<T> DeepMerge for BTreeSet<T> where T:DeepMerge + Ord {
+    type Leaf = T::Leaf;
+
+    fn deep_merge_with<F>(mut self, other:Self, p:&MergePolicy, _:&mut F) -> Self where F:FnMut(T::Leaf, T::Leaf) -> T::Leaf
+    {if p.seqs == SeqPolicy::Replace {other} else {self.extend(other); self}}
+}
+
+/// merges `b` into `a` entry by entry, where `remove` takes an entry out of
+/// `a` for [`NilPolicy::Delete`](enum.NilPolicy.html#variant.Delete).
+fn merge_map<M,K,V,R,F>(mut a:M, b:M, p:&MergePolicy, remove:R, f:&mut F) -> M
+    where M:Map<K,V> + IntoIterator<Item = (K,V)>, V:DeepMerge, R:Fn(&mut M, &K) -> Option<V>, F:FnMut(V::Leaf, V::Leaf) -> V::Leaf
+{for (k,v) in b
+ {if v.is_nil() {match p.nils {NilPolicy::Delete => {remove(&mut a, &k); continue}, NilPolicy::Ignore => continue, NilPolicy::Keep => ()}}
+  a = a.update(k, |u| match u {Some(u) => u.deep_merge_with(v, p, f), None => v});}
+ a}
+
+impl<K,V> DeepMerge for HashMap<K,V> where K:Hash + Eq, V:DeepMerge {
+    type Leaf = V::Leaf;
+
+    fn deep_merge_with<F>(self, other:Self, p:&MergePolicy, f:&mut F) -> Self where F:FnMut(V::Leaf, V::Leaf) -> V::Leaf
+    {merge_map(self, other, p, HashMap::remove, f)}
+}
+
+impl<K,V> DeepMerge for BTreeMap<K,V> where K:Ord, V:DeepMerge {
+    type Leaf = V::Leaf;
+
+    fn deep_merge_with<F>(self, other:Self, p:&MergePolicy, f:&mut F) -> Self where F:FnMut(V::Leaf, V::Leaf) -> V::Leaf
+    {merge_map(self, other, p, BTreeMap::remove, f)}
+}
+
+impl<K,V> DeepMerge for VecSortedMap<K,V> where K:Ord, V:DeepMerge {
+    type Leaf = V::Leaf;
+
+    fn deep_merge_with<F>(self, other:Self, p:&MergePolicy, f:&mut F) -> Self where F:FnMut(V::Leaf, V::Leaf) -> V::Leaf
+    {merge_map(self, other, p, VecSortedMap::remove, f)}
+}
+
+/// maps merge recursively; lists and vectors, into the kind of the old one,
+/// and sets merge by the [`SeqPolicy`](enum.SeqPolicy.html); any other pair
+/// is a leaf. a new nil is kept, deletes the entry, or is ignored by the
+/// [`NilPolicy`](enum.NilPolicy.html).
+impl DeepMerge for Value {
+    type Leaf = Value;
+
+    fn deep_merge_with<F>(self, other:Value, p:&MergePolicy, f:&mut F) -> Value where F:FnMut(Value,Value) -> Value {
+        match (self, other) {
+            (Value::Map(a), Value::Map(b)) => Value::Map(a.deep_merge_with(b, p, f)),
+            (Value::Set(mut a), Value::Set(b)) =>
+                if p.seqs == SeqPolicy::Replace {Value::Set(b)} else {a.extend(b); Value::Set(a)},
+            (Value::Vector(a), Value::Vector(b)) => Value::Vector(merge_seq(a, b, p)),
+            (Value::Vector(a), Value::List(b)) => Value::Vector(merge_seq(a, b.into(), p)),
+            (Value::List(a), Value::List(b)) => Value::List(merge_seq(a, b, p)),
+            (Value::List(a), Value::Vector(b)) => Value::List(merge_seq(a, b.into(), p)),
+            (a, Value::Nil) if p.nils == NilPolicy::Ignore => a,
+            (a, b) => f(a, b),
+        }
+    }
+
+    fn is_nil(&self) -> bool
+    {self.is_nil()}
+}
+
+/// objects merge recursively, and arrays by the
+/// [`SeqPolicy`](enum.SeqPolicy.html); any other pair is a leaf. a new null is
+/// kept, deletes the entry, or is ignored by the
+/// [`NilPolicy`](enum.NilPolicy.html).
+impl DeepMerge for Json {
+    type Leaf = Json;
+
+    fn deep_merge_with<F>(self, other:Json, p:&MergePolicy, f:&mut F) -> Json where F:FnMut(Json,Json) -> Json {
+        match (self, other) {
+            (Json::Object(a), Json::Object(b)) => Json::Object(a.deep_merge_with(b, p, f)),
+            (Json::Array(a), Json::Array(b)) => Json::Array(merge_seq(a, b, p)),
+            (a, Json::Null) if p.nils == NilPolicy::Ignore => a,
+            (a, b) => f(a, b),
+        }
+    }
+
+    fn is_nil(&self) -> bool
+    {self.is_null()}
+}
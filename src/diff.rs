@@ -0,0 +1,315 @@
+use value::Value;
+use json_value::Json;
+use vec_sorted_map::VecSortedMap;
+use vec_sorted_set::VecSortedSet;
+use std::collections::{HashMap,BTreeMap,HashSet,BTreeSet,VecDeque};
+use std::iter::FromIterator;
+use std::hash::Hash;
+
+/// the parts of a [`diff`](fn.diff.html): the things only in the first, the
+/// things only in the second, and the things in both, with `None` for
+/// nothing.
+pub type Parts<P> = (Option<P>, Option<P>, Option<P>);
+
+/// values which can be compared structurally, like by `clojure`'s
+/// [`diff`](http://clojuredocs.org/clojure.data/diff): maps are compared key
+/// by key, recursively; sequences index by index, recursively; and sets as
+/// wholes. any other pair is a leaf, which is in both if equal, or in each of
+/// them otherwise. equal values are in both, even if empty.
+///
+/// # example
+/// ```
+/// use protocoll::data::Diff;
+/// use std::collections::BTreeMap;
+/// let a:BTreeMap<_,_> = vec![("x", vec![1,2,3]), ("y", vec![4])].into_iter().collect();
+/// let b:BTreeMap<_,_> = vec![("x", vec![1,5,3,6]), ("z", vec![7])].into_iter().collect();
+/// let (only_a, only_b, both) = a.diff(&b);
+/// assert_eq!(only_a.unwrap(), vec![("x", vec![None, Some(2)]), ("y", vec![Some(4)])].into_iter().collect());
+/// assert_eq!(only_b.unwrap(), vec![("x", vec![None, Some(5), None, Some(6)]), ("z", vec![Some(7)])].into_iter().collect());
+/// assert_eq!(both.unwrap(), vec![("x", vec![Some(1), None, Some(3)])].into_iter().collect());
+/// assert_eq!(Vec::<i32>::new().diff(&vec![]), (None, None, Some(vec![])));
+/// let c:BTreeMap<_,_> = vec![("e", vec![])].into_iter().collect();
+/// let d:BTreeMap<_,_> = vec![("e", vec![]), ("f", vec![8])].into_iter().collect();
+/// assert_eq!(c.diff(&d), (None, Some(vec![("f", vec![Some(8)])].into_iter().collect()), Some(c.part())));
+/// ```
+pub trait Diff {
+    /// the type of the parts, which is `Self` but for sequences, whose parts
+    /// keep the indices of their items with `None` in between.
+    type Part;
+
+    /// the things only in this one, only in `other`, and in both.
+    fn diff(&self, other:&Self) -> Parts<Self::Part>;
+
+    /// all of this one as a part.
+    fn part(&self) -> Self::Part;
+}
+
+/// the things only in `a`, only in `b`, and in both.
+///
+/// like `clojure`'s [`diff`](http://clojuredocs.org/clojure.data/diff), where
+/// nil is the value for nothing, and an entry whose value differs from nil
+/// keeps it as nil on the side it is in.
+///
+/// # example
+/// ```
+/// use protocoll::{data,edn};
+/// let a = edn::read("{:a 1, :b [1 2 3], :c #{1 2}, :d nil}").unwrap();
+/// let b = edn::read("{:a 1, :b [5 2], :c #{2 3}, :d 4}").unwrap();
+/// let (only_a, only_b, both) = data::diff(&a, &b);
+/// assert_eq!(only_a.unwrap().to_string(), "{:b [1 nil 3], :c #{1}, :d nil}");
+/// assert_eq!(only_b.unwrap().to_string(), "{:b [5], :c #{3}, :d 4}");
+/// assert_eq!(both.unwrap().to_string(), "{:a 1, :b [nil 2], :c #{2}}");
+/// assert_eq!(data::diff(&a, &a), (None, None, Some(a)));
+/// ```
+pub fn diff<T>(a:&T, b:&T) -> Parts<T::Part> where T:Diff
+{a.diff(b)}
+
+macro_rules! leaves {
+    ($($t:ty)*) => {$(
+        impl Diff for $t
+        {type Part = $t;
+         fn diff(&self, other:&$t) -> Parts<$t> {leaf(self, other)}
+         fn part(&self) -> $t {self.clone()}}
+    )*}
+}
+
+leaves!(bool char i8 i16 i32 i64 isize u8 u16 u32 u64 usize f32 f64 String);
+
+impl<'a> Diff for &'a str {type Part = &'a str; fn diff(&self, o:&&'a str) -> Parts<&'a str> {leaf(self, o)} fn part(&self) -> &'a str {self}}
+
+fn leaf<T>(a:&T, b:&T) -> Parts<T> where T:Clone + PartialEq
+{if a == b {(None, None, Some(a.clone()))} else {(Some(a.clone()), Some(b.clone()), None)}}
+
+/// `Some` unless empty.
+fn non_empty<C,E>(es:Vec<E>) -> Option<C> where C:FromIterator<E>
+{if es.is_empty() {None} else {Some(es.into_iter().collect())}}
+
+/// the entries of the parts of two maps, before they are collected.
+type EntryParts<K,P> = (Vec<(K,P)>, Vec<(K,P)>, Vec<(K,P)>);
+
+/// the entries only in `a`, only in `b`, and in both, where `get_a` and
+/// `get_b` look them up.
+fn diff_entries<'a,K,V,I,J,F,G>(a:I, b:J, get_a:F, get_b:G) -> EntryParts<K,V::Part>
+    where K:Clone + 'a, V:Diff + 'a, I:Iterator<Item = (&'a K, &'a V)>, J:Iterator<Item = (&'a K, &'a V)>,
+          F:Fn(&K) -> Option<&'a V>, G:Fn(&K) -> Option<&'a V>
+{let (mut x, mut y, mut z) = (Vec::new(), Vec::new(), Vec::new());
+ for (k,va) in a
+ {match get_b(k)
+  {None => x.push((k.clone(), va.part())),
+   Some(vb) =>
+   {let (p,q,r) = va.diff(vb);
+    if let Some(p) = p {x.push((k.clone(), p))}
+    if let Some(q) = q {y.push((k.clone(), q))}
+    if let Some(r) = r {z.push((k.clone(), r))}}}}
+ for (k,vb) in b {if get_a(k).is_none() {y.push((k.clone(), vb.part()))}}
+ (x, y, z)}
+
+/// the index-wise diff of two sequences, without the trailing `None`s.
+fn diff_items<'a,T,I>(mut a:I, mut b:I) -> Parts<Vec<Option<T::Part>>> where T:Diff + 'a, I:Iterator<Item = &'a T>
+{let (mut x, mut y, mut z) = (Vec::new(), Vec::new(), Vec::new());
+ loop
+ {let (p,q,r) = match (a.next(), b.next())
+  {(None, None) => break,
+   (Some(s), None) => (Some(s.part()), None, None),
+   (None, Some(t)) => (None, Some(t.part()), None),
+   (Some(s), Some(t)) => s.diff(t)};
+  x.push(p); y.push(q); z.push(r);}
+ (trim(x), trim(y), trim(z))}
+
+fn trim<P>(mut v:Vec<Option<P>>) -> Option<Vec<Option<P>>>
+{while let Some(&None) = v.last() {v.pop();}
+ if v.is_empty() {None} else {Some(v)}}
+
+/// the parts have `None` at the indices of the items not in them.
+impl<T> Diff for Vec<T> where T:Diff + PartialEq {
+    type Part = Vec<Option<T::Part>>;
+
+    fn diff(&self, other:&Self) -> Parts<Self::Part>
+    {if self == other {return (None, None, Some(self.part()))}
+     diff_items(self.iter(), other.iter())}
+
+    fn part(&self) -> Self::Part
+    {self.iter().map(|t| Some(t.part())).collect()}
+}
+
+/// the parts have `None` at the indices of the items not in them.
+impl<T> Diff for VecDeque<T> where T:Diff + PartialEq {
+    type Part = Vec<Option<T::Part>>;
+
+    fn diff(&self, other:&Self) -> Parts<Self::Part>
+    {if self == other {return (None, None, Some(self.part()))}
+     diff_items(self.iter(), other.iter())}
+
+    fn part(&self) -> Self::Part
+    {self.iter().map(|t| Some(t.part())).collect()}
+}
+
+/// the differences and the intersection; the items are not compared
+/// recursively.
+impl<T> Diff for HashSet<T> where T:Hash + Eq + Clone {
+    type Part = HashSet<T>;
+
+    fn diff(&self, other:&Self) -> Parts<Self>
+    {if self == other {return (None, None, Some(self.part()))}
+     (non_empty(self.difference(other).cloned().collect()),
+      non_empty(other.difference(self).cloned().collect()),
+      non_empty(self.intersection(other).cloned().collect()))}
+
+    fn part(&self) -> Self
+    {self.clone()}
+}
+
+/// the differences and the intersection; the items are not compared
+/// recursively.
+impl<T> Diff for BTreeSet<T> where T:Ord + Clone {
+    type Part = BTreeSet<T>;
+
+    fn diff(&self, other:&Self) -> Parts<Self>
+    {if self == other {return (None, None, Some(self.part()))}
+     (non_empty(self.difference(other).cloned().collect()),
+      non_empty(other.difference(self).cloned().collect()),
+      non_empty(self.intersection(other).cloned().collect()))}
+
+    fn part(&self) -> Self
+    {self.clone()}
+}
+
+/// the differences and the intersection; the items are not compared
+/// recursively.
+impl<T> Diff for VecSortedSet<T> where T:Ord + Clone {
+    type Part = VecSortedSet<T>;
+
+    fn diff(&self, other:&Self) -> Parts<Self>
+    {if self == other {return (None, None, Some(self.part()))}
+     (non_empty(self.difference(other).cloned().collect()),
+      non_empty(other.difference(self).cloned().collect()),
+      non_empty(self.intersection(other).cloned().collect()))}
+
+    fn part(&self) -> Self
+    {self.clone()}
+}
+
+impl<K,V> Diff for HashMap<K,V> where K:Hash + Eq + Clone, V:Diff + PartialEq {
+    type Part = HashMap<K,V::Part>;
+
+    fn diff(&self, other:&Self) -> Parts<Self::Part>
+    {if self == other {return (None, None, Some(self.part()))}
+     let (x,y,z) = diff_entries(self.iter(), other.iter(), |k| self.get(k), |k| other.get(k));
+     (non_empty(x), non_empty(y), non_empty(z))}
+
+    fn part(&self) -> Self::Part
+    {self.iter().map(|(k,v)| (k.clone(), v.part())).collect()}
+}
+
+impl<K,V> Diff for BTreeMap<K,V> where K:Ord + Clone, V:Diff + PartialEq {
+    type Part = BTreeMap<K,V::Part>;
+
+    fn diff(&self, other:&Self) -> Parts<Self::Part>
+    {if self == other {return (None, None, Some(self.part()))}
+     let (x,y,z) = diff_entries(self.iter(), other.iter(), |k| self.get(k), |k| other.get(k));
+     (non_empty(x), non_empty(y), non_empty(z))}
+
+    fn part(&self) -> Self::Part
+    {self.iter().map(|(k,v)| (k.clone(), v.part())).collect()}
+}
+
+impl<K,V> Diff for VecSortedMap<K,V> where K:Ord + Clone, V:Diff + PartialEq {
+    type Part = VecSortedMap<K,V::Part>;
+
+    fn diff(&self, other:&Self) -> Parts<Self::Part>
+    {if self == other {return (None, None, Some(self.part()))}
+     let (x,y,z) = diff_entries(self.iter().map(|e| (&e.0, &e.1)), other.iter().map(|e| (&e.0, &e.1)),
+                                |k| self.get(k), |k| other.get(k));
+     (non_empty(x), non_empty(y), non_empty(z))}
+
+    fn part(&self) -> Self::Part
+    {self.iter().map(|e| (e.0.clone(), e.1.part())).collect()}
+}
+
+/// the parts of an entry of a map or sequence of dynamic values, where the
+/// default is nil, as `clojure`'s `diff-associative-key` makes them: a
+/// present entry whose value differs from nil, the missing one, keeps nil.
+fn diff_key<T>(a:Option<&T>, b:Option<&T>) -> Parts<T> where T:Diff<Part = T> + Default
+{let nil = T::default();
+ let (x,y,z) = a.unwrap_or(&nil).diff(b.unwrap_or(&nil));
+ let same = a.is_some() && b.is_some() && z.is_some();
+ (if a.is_some() && (x.is_some() || !same) {Some(x.unwrap_or_default())} else {None},
+  if b.is_some() && (y.is_some() || !same) {Some(y.unwrap_or_default())} else {None},
+  if same {z} else {None})}
+
+/// the parts of two sequences of dynamic values as vectors, padded with nil.
+fn diff_seq<T>(a:&[T], b:&[T]) -> Parts<Vec<T>> where T:Diff<Part = T> + Default
+{let (mut x, mut y, mut z) = (Vec::new(), Vec::new(), Vec::new());
+ for i in 0..a.len().max(b.len())
+ {let (p,q,r) = diff_key(a.get(i), b.get(i));
+  x.push(p); y.push(q); z.push(r);}
+ let pad = |v:Vec<Option<T>>| trim(v).map(|v| v.into_iter().map(Option::unwrap_or_default).collect());
+ (pad(x), pad(y), pad(z))}
+
+/// the parts of two maps of dynamic values.
+fn diff_map<K,T>(a:&VecSortedMap<K,T>, b:&VecSortedMap<K,T>) -> Parts<VecSortedMap<K,T>>
+    where K:Ord + Clone, T:Diff<Part = T> + Default
+{let (mut x, mut y, mut z) = (Vec::new(), Vec::new(), Vec::new());
+ let keys = a.iter().map(|e| &e.0).chain(b.iter().map(|e| &e.0).filter(|k| !a.contains_key(*k)));
+ for k in keys
+ {let (p,q,r) = diff_key(a.get(k), b.get(k));
+  if let Some(p) = p {x.push((k.clone(), p))}
+  if let Some(q) = q {y.push((k.clone(), q))}
+  if let Some(r) = r {z.push((k.clone(), r))}}
+ (non_empty(x), non_empty(y), non_empty(z))}
+
+/// like `clojure`'s [`diff`](http://clojuredocs.org/clojure.data/diff): lists
+/// and vectors are both sequential and their parts are vectors, with nil at
+/// the indices not in them. nil is the value for nothing, so it is never a
+/// part of its own, but when two values are equal, they are in both, even if
+/// nil.
+impl Diff for Value {
+    type Part = Value;
+
+    fn diff(&self, other:&Value) -> Parts<Value> {
+        if self == other {return (None, None, Some(self.clone()))}
+        let some = |v:&Value| if v.is_nil() {None} else {Some(v.clone())};
+        match (self, other) {
+            (Value::Map(a), Value::Map(b)) =>
+            {let (x,y,z) = diff_map(a, b);
+             (x.map(Value::Map), y.map(Value::Map), z.map(Value::Map))}
+            (Value::Set(a), Value::Set(b)) =>
+            {let (x,y,z) = a.diff(b);
+             (x.map(Value::Set), y.map(Value::Set), z.map(Value::Set))}
+            (Value::Vector(_), Value::Vector(_)) | (Value::Vector(_), Value::List(_)) |
+            (Value::List(_), Value::Vector(_)) | (Value::List(_), Value::List(_)) =>
+            {let items = |v:&Value| match v {Value::List(l) => l.iter().cloned().collect(), Value::Vector(v) => v.clone(), _ => Vec::new()};
+             let (x,y,z) = diff_seq(&items(self), &items(other));
+             (x.map(Value::Vector), y.map(Value::Vector), z.map(Value::Vector))}
+            (a, b) => (some(a), some(b), None),
+        }
+    }
+
+    fn part(&self) -> Value
+    {self.clone()}
+}
+
+/// objects are compared like maps and arrays like sequences, padded with
+/// null; null is the value for nothing, as nil is for
+/// [`Value`](../data/enum.Value.html).
+impl Diff for Json {
+    type Part = Json;
+
+    fn diff(&self, other:&Json) -> Parts<Json> {
+        if self == other {return (None, None, Some(self.clone()))}
+        match (self, other) {
+            (Json::Object(a), Json::Object(b)) =>
+            {let (x,y,z) = diff_map(a, b);
+             (x.map(Json::Object), y.map(Json::Object), z.map(Json::Object))}
+            (Json::Array(a), Json::Array(b)) =>
+            {let (x,y,z) = diff_seq(a, b);
+             (x.map(Json::Array), y.map(Json::Array), z.map(Json::Array))}
+            (a, b) =>
+            {let some = |v:&Json| if v.is_null() {None} else {Some(v.clone())};
+             (some(a), some(b), None)}
+        }
+    }
+
+    fn part(&self) -> Json
+    {self.clone()}
+}
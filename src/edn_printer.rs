@@ -0,0 +1,200 @@
+use value::Value;
+use keyword::{Keyword,Symbol};
+use vec_sorted_map::VecSortedMap;
+use vec_sorted_set::VecSortedSet;
+use std::collections::{HashMap,BTreeMap,HashSet,BTreeSet,VecDeque,LinkedList};
+use std::hash::Hash;
+use std::fmt::{self,Display,Formatter,Write};
+
+/// printing as [edn](https://github.com/edn-format/edn).
+///
+/// seqs print as vectors, except for the lists of a
+/// [`Value`](../data/enum.Value.html). what is printed reads back with
+/// [`read`](fn.read.html) into the equal `Value`, as long as the keywords
+/// and symbols are valid edn, and the ints fit into an `i64`. infinite floats
+/// and `NaN` are printed as `##Inf`, `##-Inf` and `##NaN`.
+///
+/// # example
+/// ```
+/// use protocoll::Map;
+/// use protocoll::map::VecSortedMap;
+/// use protocoll::edn::{self,Edn};
+/// use protocoll::string::Keyword;
+/// let m = VecSortedMap::new()
+///     .inc(Keyword::new("name"), vec![Some("ann\n"), None])
+///     .inc(Keyword::with_ns("user", "id"), vec![]);
+/// let s = m.to_edn();
+/// assert_eq!(s, r#"{:name ["ann\n" nil], :user/id []}"#);
+/// assert_eq!(edn::read(&s).unwrap().to_edn(), s);
+/// assert_eq!((1.0, 'x', f64::NAN).to_edn(), r"[1.0 \x ##NaN]");
+/// ```
+pub trait Edn {
+    fn write_edn<W:Write>(&self, w:&mut W) -> fmt::Result;
+
+    fn to_edn(&self) -> String
+    {let mut s = String::new(); self.write_edn(&mut s).unwrap(); s}
+}
+
+fn write_seq<'a,W,T,I>(w:&mut W, open:&str, close:&str, items:I) -> fmt::Result
+    where W:Write, T:Edn + ?Sized + 'a, I:IntoIterator<Item = &'a T>
+{w.write_str(open)?;
+ for (i,t) in items.into_iter().enumerate() {if i > 0 {w.write_char(' ')?} t.write_edn(w)?}
+ w.write_str(close)}
+
+fn write_map<'a,W,K,V,I>(w:&mut W, entries:I) -> fmt::Result
+    where W:Write, K:Edn + 'a, V:Edn + 'a, I:IntoIterator<Item = (&'a K, &'a V)>
+{w.write_char('{')?;
+ for (i,(k,v)) in entries.into_iter().enumerate()
+ {if i > 0 {w.write_str(", ")?} k.write_edn(w)?; w.write_char(' ')?; v.write_edn(w)?}
+ w.write_char('}')}
+
+impl Edn for str {
+    fn write_edn<W:Write>(&self, w:&mut W) -> fmt::Result {
+        w.write_char('"')?;
+        for c in self.chars() {
+            match c {
+                '"' => w.write_str("\\\"")?,
+                '\\' => w.write_str("\\\\")?,
+                '\n' => w.write_str("\\n")?,
+                '\t' => w.write_str("\\t")?,
+                '\r' => w.write_str("\\r")?,
+                c if c.is_control() => write!(w, "\\u{:04x}", c as u32)?,
+                c => w.write_char(c)?
+            }
+        }
+        w.write_char('"')
+    }
+}
+
+impl Edn for String
+{fn write_edn<W:Write>(&self, w:&mut W) -> fmt::Result {self.as_str().write_edn(w)}}
+
+impl Edn for char {
+    fn write_edn<W:Write>(&self, w:&mut W) -> fmt::Result {
+        match *self {
+            '\n' => w.write_str("\\newline"),
+            '\r' => w.write_str("\\return"),
+            ' ' => w.write_str("\\space"),
+            '\t' => w.write_str("\\tab"),
+            '\u{c}' => w.write_str("\\formfeed"),
+            '\u{8}' => w.write_str("\\backspace"),
+            c if c.is_control() || c.is_whitespace() => write!(w, "\\u{:04x}", c as u32),
+            c => write!(w, "\\{}", c)
+        }
+    }
+}
+
+impl Edn for bool
+{fn write_edn<W:Write>(&self, w:&mut W) -> fmt::Result {write!(w, "{}", self)}}
+
+impl Edn for i8 {fn write_edn<W:Write>(&self, w:&mut W) -> fmt::Result {write!(w, "{}", self)}}
+impl Edn for i16 {fn write_edn<W:Write>(&self, w:&mut W) -> fmt::Result {write!(w, "{}", self)}}
+impl Edn for i32 {fn write_edn<W:Write>(&self, w:&mut W) -> fmt::Result {write!(w, "{}", self)}}
+impl Edn for i64 {fn write_edn<W:Write>(&self, w:&mut W) -> fmt::Result {write!(w, "{}", self)}}
+impl Edn for isize {fn write_edn<W:Write>(&self, w:&mut W) -> fmt::Result {write!(w, "{}", self)}}
+impl Edn for u8 {fn write_edn<W:Write>(&self, w:&mut W) -> fmt::Result {write!(w, "{}", self)}}
+impl Edn for u16 {fn write_edn<W:Write>(&self, w:&mut W) -> fmt::Result {write!(w, "{}", self)}}
+impl Edn for u32 {fn write_edn<W:Write>(&self, w:&mut W) -> fmt::Result {write!(w, "{}", self)}}
+impl Edn for usize {fn write_edn<W:Write>(&self, w:&mut W) -> fmt::Result {write!(w, "{}", self)}}
+
+/// beyond `i64::MAX` this prints with an `N`, which does not read back.
+impl Edn for u64
+{fn write_edn<W:Write>(&self, w:&mut W) -> fmt::Result
+ {if *self > i64::MAX as u64 {write!(w, "{}N", self)} else {write!(w, "{}", self)}}}
+
+impl Edn for f64 {
+    fn write_edn<W:Write>(&self, w:&mut W) -> fmt::Result {
+        if self.is_nan() {w.write_str("##NaN")}
+        else if self.is_infinite() {w.write_str(if *self > 0.0 {"##Inf"} else {"##-Inf"})}
+        else {write!(w, "{:?}", self)}
+    }
+}
+
+impl Edn for f32
+{fn write_edn<W:Write>(&self, w:&mut W) -> fmt::Result {(*self as f64).write_edn(w)}}
+
+impl Edn for Keyword
+{fn write_edn<W:Write>(&self, w:&mut W) -> fmt::Result {write!(w, "{}", self)}}
+
+impl Edn for Symbol
+{fn write_edn<W:Write>(&self, w:&mut W) -> fmt::Result {write!(w, "{}", self)}}
+
+impl<T> Edn for &T where T:Edn + ?Sized
+{fn write_edn<W:Write>(&self, w:&mut W) -> fmt::Result {(**self).write_edn(w)}}
+
+impl<T> Edn for Box<T> where T:Edn + ?Sized
+{fn write_edn<W:Write>(&self, w:&mut W) -> fmt::Result {(**self).write_edn(w)}}
+
+impl<T> Edn for Option<T> where T:Edn
+{fn write_edn<W:Write>(&self, w:&mut W) -> fmt::Result
+ {match *self {Some(ref t) => t.write_edn(w), None => w.write_str("nil")}}}
+
+impl Edn for ()
+{fn write_edn<W:Write>(&self, w:&mut W) -> fmt::Result {w.write_str("nil")}}
+
+impl<A,B> Edn for (A,B) where A:Edn, B:Edn
+{fn write_edn<W:Write>(&self, w:&mut W) -> fmt::Result
+ {w.write_char('[')?; self.0.write_edn(w)?; w.write_char(' ')?; self.1.write_edn(w)?; w.write_char(']')}}
+
+impl<A,B,C> Edn for (A,B,C) where A:Edn, B:Edn, C:Edn
+{fn write_edn<W:Write>(&self, w:&mut W) -> fmt::Result
+ {w.write_char('[')?; self.0.write_edn(w)?; w.write_char(' ')?; self.1.write_edn(w)?;
+  w.write_char(' ')?; self.2.write_edn(w)?; w.write_char(']')}}
+
+impl<T> Edn for [T] where T:Edn
+{fn write_edn<W:Write>(&self, w:&mut W) -> fmt::Result {write_seq(w, "[", "]", self)}}
+
+impl<T, const N:usize> Edn for [T;N] where T:Edn
+{fn write_edn<W:Write>(&self, w:&mut W) -> fmt::Result {write_seq(w, "[", "]", self)}}
+
+impl<T> Edn for Vec<T> where T:Edn
+{fn write_edn<W:Write>(&self, w:&mut W) -> fmt::Result {write_seq(w, "[", "]", self)}}
+
+impl<T> Edn for VecDeque<T> where T:Edn
+{fn write_edn<W:Write>(&self, w:&mut W) -> fmt::Result {write_seq(w, "[", "]", self)}}
+
+impl<T> Edn for LinkedList<T> where T:Edn
+{fn write_edn<W:Write>(&self, w:&mut W) -> fmt::Result {write_seq(w, "[", "]", self)}}
+
+impl<T,S> Edn for HashSet<T,S> where T:Edn + Hash + Eq
+{fn write_edn<W:Write>(&self, w:&mut W) -> fmt::Result {write_seq(w, "#{", "}", self)}}
+
+impl<T> Edn for BTreeSet<T> where T:Edn
+{fn write_edn<W:Write>(&self, w:&mut W) -> fmt::Result {write_seq(w, "#{", "}", self)}}
+
+impl<T> Edn for VecSortedSet<T> where T:Edn
+{fn write_edn<W:Write>(&self, w:&mut W) -> fmt::Result {write_seq(w, "#{", "}", self)}}
+
+impl<K,V,S> Edn for HashMap<K,V,S> where K:Edn + Hash + Eq, V:Edn
+{fn write_edn<W:Write>(&self, w:&mut W) -> fmt::Result {write_map(w, self)}}
+
+impl<K,V> Edn for BTreeMap<K,V> where K:Edn, V:Edn
+{fn write_edn<W:Write>(&self, w:&mut W) -> fmt::Result {write_map(w, self)}}
+
+impl<K,V> Edn for VecSortedMap<K,V> where K:Edn, V:Edn
+{fn write_edn<W:Write>(&self, w:&mut W) -> fmt::Result
+ {write_map(w, self.iter().map(|(k,v)| (k,v)))}}
+
+impl Edn for Value {
+    fn write_edn<W:Write>(&self, w:&mut W) -> fmt::Result {
+        match *self {
+            Value::Nil => w.write_str("nil"),
+            Value::Bool(b) => b.write_edn(w),
+            Value::Int(n) => n.write_edn(w),
+            Value::Float(x) => x.write_edn(w),
+            Value::Char(c) => c.write_edn(w),
+            Value::Str(ref s) => s.write_edn(w),
+            Value::Keyword(k) => k.write_edn(w),
+            Value::Symbol(s) => s.write_edn(w),
+            Value::List(ref l) => write_seq(w, "(", ")", l),
+            Value::Vector(ref v) => v.write_edn(w),
+            Value::Map(ref m) => m.write_edn(w),
+            Value::Set(ref s) => s.write_edn(w),
+            Value::Tagged(t, ref v) => {write!(w, "#{} ", t)?; v.write_edn(w)}
+        }
+    }
+}
+
+/// prints as edn.
+impl Display for Value
+{fn fmt(&self, fmt:&mut Formatter) -> fmt::Result {self.write_edn(fmt)}}
@@ -0,0 +1,420 @@
+use value::Value;
+use keyword::{Keyword,Symbol};
+use vec_sorted_map::VecSortedMap;
+use vec_sorted_set::VecSortedSet;
+use std::collections::{HashMap,VecDeque};
+use std::error;
+use std::fmt::{self,Display,Formatter};
+
+/// an error while reading, at a 1-based line and column counted in chars.
+#[derive(Clone,PartialEq,Eq,Debug)]
+pub struct Error {pub line:usize, pub column:usize, pub msg:String}
+
+impl Display for Error
+{fn fmt(&self, fmt:&mut Formatter) -> fmt::Result
+ {write!(fmt, "{}:{}: {}", self.line, self.column, self.msg)}}
+
+impl error::Error for Error {}
+
+type Handler = Box<dyn Fn(Value) -> Result<Value,String>>;
+
+/// reads [edn](https://github.com/edn-format/edn) into
+/// [`Value`](../data/enum.Value.html)s.
+///
+/// lists, vectors, maps and sets are read into the corresponding variants,
+/// with duplicate keys in a map or items in a set being errors. numbers are
+/// read as `i64` or `f64`; the `N` and `M` suffixes are accepted but the
+/// numbers must still fit. `#_` discards the next form, and namespaced maps
+/// like `#:user{:id 1}` qualify their keys.
+///
+/// a tagged literal is passed to the handler registered for its tag, or kept
+/// as a [`Value::Tagged`](../data/enum.Value.html#variant.Tagged) if there is
+/// none. the handlers for `#inst` and `#uuid` check the format and keep them
+/// tagged, the uuid in lower case.
+///
+/// forms nested more than [`max_depth`](#method.max_depth) deep are an error
+/// rather than a stack overflow.
+///
+/// # example
+/// ```
+/// use protocoll::data::Value;
+/// use protocoll::edn::Reader;
+/// use protocoll::string::{Keyword,Symbol};
+/// let r = Reader::new().tag("point", |v| match v
+///     {Value::Vector(ref xy) if xy.len() == 2 => Ok(Value::Vector(xy.clone())),
+///      _ => Err("a point is a vector of two".to_owned())});
+/// let v = r.read("#:user{:id 7, :pos #point [1 2] ; where\n #_ :ignored :_/x #{}}").unwrap();
+/// assert_eq!(v.get(&Value::Keyword(Keyword::parse("user/id"))), Some(&Value::Int(7)));
+/// assert_eq!(v.get(&Value::Keyword(Keyword::parse("user/pos"))).map(Value::len), Some(2));
+/// assert!(v.get(&Value::Keyword(Keyword::new("x"))).is_some());
+/// let e = r.read("[1 2\n #point 3]").unwrap_err();
+/// assert_eq!((e.line, e.column), (2, 2));
+/// assert_eq!(r.read("#my/tag 1").unwrap(),
+///            Value::Tagged(Symbol::parse("my/tag"), Box::new(Value::Int(1))));
+/// let e = r.read("#:a{:x 1, :a/x 2}").unwrap_err();
+/// assert_eq!((e.column, e.msg.as_str()), (11, "duplicate key in map"));
+/// assert!(r.read("#inst \"2024-01-01T00:00:0\u{e9}\"").is_err());
+/// ```
+pub struct Reader {tags:HashMap<Symbol,Handler>, max_depth:usize}
+
+impl Default for Reader
+{fn default() -> Reader {Reader::new()}}
+
+impl Reader {
+    /// a reader with the handlers for `#inst` and `#uuid`.
+    pub fn new() -> Reader
+    {Reader{tags:HashMap::new(), max_depth:256}
+     .tag("inst", |v| match v
+          {Value::Str(ref s) if is_inst(s) => Ok(Value::Tagged(Symbol::new("inst"), Box::new(v.clone()))),
+           _ => Err("#inst takes an rfc 3339 timestamp string".to_owned())})
+     .tag("uuid", |v| match v
+          {Value::Str(ref s) if is_uuid(s) =>
+           Ok(Value::Tagged(Symbol::new("uuid"), Box::new(Value::Str(s.to_lowercase())))),
+           _ => Err("#uuid takes a string of 32 hex digits grouped 8-4-4-4-12".to_owned())})}
+
+    /// registers `f` as the handler for `#tag`, replacing any previous one.
+    /// the error message it returns is reported at the position of the tag.
+    pub fn tag<F>(mut self, tag:&str, f:F) -> Reader where F:Fn(Value) -> Result<Value,String> + 'static
+    {self.tags.insert(Symbol::parse(tag), Box::new(f)); self}
+
+    /// sets how many forms may be nested in one another, counting the
+    /// innermost one and any discarded by `#_`. it is 256 by default.
+    ///
+    /// # example
+    /// ```
+    /// use protocoll::edn::{self,Reader};
+    /// assert!(edn::read(&"[".repeat(200000)).is_err());
+    /// let r = Reader::new().max_depth(3);
+    /// assert!(r.read("[[1]]").is_ok());
+    /// assert_eq!(r.read("[[[1]]]").unwrap_err().column, 4);
+    /// ```
+    pub fn max_depth(mut self, n:usize) -> Reader
+    {self.max_depth = n; self}
+
+    /// reads exactly one form. whitespace and comments may surround it.
+    pub fn read(&self, s:&str) -> Result<Value,Error>
+    {let mut p = Parser::new(self, s);
+     let v = match p.next(None)? {Some(v) => v, None => return Err(p.error("expected a form"))};
+     match p.next(None)?
+     {None => Ok(v),
+      Some(_) => Err(Error{line:p.start.0, column:p.start.1, msg:"expected only one form".to_owned()})}}
+
+    /// reads all forms.
+    pub fn read_all(&self, s:&str) -> Result<Vec<Value>,Error>
+    {let mut p = Parser::new(self, s);
+     let mut vs = Vec::new();
+     while let Some(v) = p.next(None)? {vs.push(v)}
+     Ok(vs)}
+}
+
+/// reads exactly one form with the default [`Reader`](struct.Reader.html).
+pub fn read(s:&str) -> Result<Value,Error>
+{Reader::new().read(s)}
+
+/// reads all forms with the default [`Reader`](struct.Reader.html).
+pub fn read_all(s:&str) -> Result<Vec<Value>,Error>
+{Reader::new().read_all(s)}
+
+struct Parser<'a> {
+    reader:&'a Reader,
+    s:&'a str,
+    pos:usize,
+    line:usize,
+    column:usize,
+    /// where the last form started.
+    start:(usize,usize),
+    /// how many forms are being read.
+    depth:usize,
+}
+
+fn is_delimiter(c:char) -> bool
+{c.is_whitespace() || "(),[]{}\";".contains(c)}
+
+fn is_symbol_char(c:char) -> bool
+{c.is_alphanumeric() || ".*+!-_?$%&=<>/:#'".contains(c)}
+
+impl<'a> Parser<'a> {
+    fn new(reader:&'a Reader, s:&'a str) -> Parser<'a>
+    {Parser{reader, s, pos:0, line:1, column:1, start:(1,1), depth:0}}
+
+    fn error(&self, msg:&str) -> Error
+    {Error{line:self.line, column:self.column, msg:msg.to_owned()}}
+
+    fn error_at(&self, at:(usize,usize), msg:String) -> Error
+    {Error{line:at.0, column:at.1, msg}}
+
+    fn peek(&self) -> Option<char>
+    {self.s[self.pos..].chars().next()}
+
+    fn bump(&mut self) -> Option<char>
+    {let c = self.peek()?;
+     self.pos += c.len_utf8();
+     if c == '\n' {self.line += 1; self.column = 1} else {self.column += 1}
+     Some(c)}
+
+    fn skip_space(&mut self)
+    {while let Some(c) = self.peek()
+     {if c == ';' {while self.peek().is_some_and(|c| c != '\n') {self.bump();}}
+      else if c.is_whitespace() || c == ',' {self.bump();}
+      else {break}}}
+
+    /// the next form, or `None` at `close` or at the end if `close` is `None`.
+    /// `close` comes with where the collection was opened.
+    fn next(&mut self, close:Option<(char,(usize,usize))>) -> Result<Option<Value>,Error> {
+        loop {
+            self.skip_space();
+            self.start = (self.line, self.column);
+            match (self.peek(), close) {
+                (None, None) => return Ok(None),
+                (None, Some((c,(l,k)))) => return Err(self.error(&format!("expected {} to close the collection at {}:{}", c, l, k))),
+                (Some(c), Some((d,_))) if c == d => {self.bump(); return Ok(None)}
+                (Some(c), _) if ")]}".contains(c) => return Err(self.error(&format!("unexpected {}", c))),
+                _ => ()
+            }
+            if self.s[self.pos..].starts_with("#_") {
+                self.bump(); self.bump();
+                self.nested(Parser::form)?;
+            } else {
+                return self.dispatch().map(Some)
+            }
+        }
+    }
+
+    /// a form which must be there.
+    fn form(&mut self) -> Result<Value,Error>
+    {match self.next(None)?
+     {Some(v) => Ok(v),
+      None => Err(self.error("expected a form before the end"))}}
+
+    /// `f` one level deeper, or an error if that is too deep.
+    fn nested<F>(&mut self, f:F) -> Result<Value,Error> where F:FnOnce(&mut Self) -> Result<Value,Error>
+    {if self.depth >= self.reader.max_depth {return Err(self.error("forms nested too deeply"))}
+     self.depth += 1;
+     let v = f(self);
+     self.depth -= 1;
+     v}
+
+    fn dispatch(&mut self) -> Result<Value,Error> {
+        let start = self.start;
+        self.nested(|p| match p.peek().unwrap() {
+            '(' => {p.bump(); p.items((')', start)).map(|l| Value::List(VecDeque::from(l)))}
+            '[' => {p.bump(); p.items((']', start)).map(Value::Vector)}
+            '{' => {p.bump(); p.map(start, None)}
+            '"' => {p.bump(); p.string().map(Value::Str)}
+            '\\' => {p.bump(); p.character()}
+            '#' => {p.bump(); p.hash(start)}
+            _ => {let t = p.token(); p.atom(t, start)}
+        })
+    }
+
+    fn items(&mut self, close:(char,(usize,usize))) -> Result<Vec<Value>,Error>
+    {let mut vs = Vec::new();
+     while let Some(v) = self.next(Some(close))? {vs.push(v)}
+     Ok(vs)}
+
+    /// a map whose keys are qualified by `ns`, if any.
+    fn map(&mut self, start:(usize,usize), ns:Option<&str>) -> Result<Value,Error> {
+        let mut m = VecSortedMap::new();
+        while let Some(k) = self.next(Some(('}', start)))? {
+            let at = self.start;
+            let k = match ns {Some(ns) => qualify(ns, k), None => k};
+            let v = match self.next(Some(('}', start)))?
+            {Some(v) => v, None => return Err(self.error_at(start, "a map needs an even number of forms".to_owned()))};
+            if m.insert(k, v).is_some() {return Err(self.error_at(at, "duplicate key in map".to_owned()))}
+        }
+        Ok(Value::Map(m))
+    }
+
+    fn set(&mut self, start:(usize,usize)) -> Result<Value,Error> {
+        let mut s = VecSortedSet::new();
+        while let Some(v) = self.next(Some(('}', start)))? {
+            let at = self.start;
+            if s.insert(v).is_some() {return Err(self.error_at(at, "duplicate item in set".to_owned()))}
+        }
+        Ok(Value::Set(s))
+    }
+
+    fn hash(&mut self, start:(usize,usize)) -> Result<Value,Error> {
+        match self.peek() {
+            Some('{') => {self.bump(); self.set(start)}
+            Some('#') => {
+                self.bump();
+                match self.token() {
+                    "Inf" => Ok(Value::Float(f64::INFINITY)),
+                    "-Inf" => Ok(Value::Float(f64::NEG_INFINITY)),
+                    "NaN" => Ok(Value::Float(f64::NAN)),
+                    t => Err(self.error_at(start, format!("unknown symbolic value ##{}", t)))
+                }
+            }
+            Some(':') => {
+                self.bump();
+                let ns = self.token();
+                if ns.is_empty() || !is_symbol(ns) || ns.contains('/')
+                {return Err(self.error_at(start, format!("invalid namespace for map #:{}", ns)))}
+                self.skip_space();
+                if self.peek() != Some('{') {return Err(self.error("expected a map after #:ns"))}
+                let at = (self.line, self.column);
+                self.bump();
+                self.map(at, Some(ns))
+            }
+            Some(c) if c.is_alphabetic() => {
+                let t = self.token();
+                if !is_symbol(t) {return Err(self.error_at(start, format!("invalid tag #{}", t)))}
+                let tag = Symbol::parse(t);
+                let v = self.form()?;
+                match self.reader.tags.get(&tag)
+                {Some(f) => f(v).map_err(|msg| self.error_at(start, msg)),
+                 None => Ok(Value::Tagged(tag, Box::new(v)))}
+            }
+            _ => Err(self.error_at(start, "invalid dispatch after #".to_owned()))
+        }
+    }
+
+    fn token(&mut self) -> &'a str
+    {let from = self.pos;
+     while self.peek().is_some_and(|c| !is_delimiter(c)) {self.bump();}
+     &self.s[from..self.pos]}
+
+    fn atom(&self, t:&str, start:(usize,usize)) -> Result<Value,Error> {
+        let mut cs = t.chars();
+        let (c0,c1) = (cs.next(), cs.next());
+        let numeric = c0.is_some_and(|c| c.is_ascii_digit())
+            || (c0.is_some_and(|c| c == '+' || c == '-') && c1.is_some_and(|c| c.is_ascii_digit()));
+        if numeric {return number(t).map_err(|msg| self.error_at(start, msg))}
+        match t {
+            "nil" => Ok(Value::Nil),
+            "true" => Ok(Value::Bool(true)),
+            "false" => Ok(Value::Bool(false)),
+            _ if t.starts_with(':') =>
+                if is_symbol(&t[1..]) && !t[1..].starts_with(':') && t != ":/"
+                {Ok(Value::Keyword(Keyword::parse(&t[1..])))}
+                else {Err(self.error_at(start, format!("invalid keyword {}", t)))},
+            _ if is_symbol(t) => Ok(Value::Symbol(Symbol::parse(t))),
+            _ => Err(self.error_at(start, format!("invalid token {}", t)))
+        }
+    }
+
+    fn string(&mut self) -> Result<String,Error> {
+        let mut s = String::new();
+        loop {
+            let at = (self.line, self.column);
+            match self.bump() {
+                None => return Err(self.error_at(self.start, "unterminated string".to_owned())),
+                Some('"') => return Ok(s),
+                Some('\\') => match self.bump() {
+                    Some('t') => s.push('\t'),
+                    Some('r') => s.push('\r'),
+                    Some('n') => s.push('\n'),
+                    Some('b') => s.push('\u{8}'),
+                    Some('f') => s.push('\u{c}'),
+                    Some('\\') => s.push('\\'),
+                    Some('"') => s.push('"'),
+                    Some('u') => {
+                        let hex:String = (0..4).filter_map(|_| self.bump()).collect();
+                        match u32::from_str_radix(&hex, 16).ok().and_then(::std::char::from_u32)
+                        {Some(c) if hex.len() == 4 => s.push(c),
+                         _ => return Err(self.error_at(at, format!("invalid unicode escape \\u{}", hex)))}
+                    }
+                    c => return Err(self.error_at(at, format!("invalid escape \\{}", c.map_or(String::new(), String::from))))
+                },
+                Some(c) => s.push(c)
+            }
+        }
+    }
+
+    fn character(&mut self) -> Result<Value,Error> {
+        let start = self.start;
+        let c = match self.bump() {Some(c) => c, None => return Err(self.error("expected a char"))};
+        let rest = self.token();
+        let t = &self.s[self.pos - rest.len() - c.len_utf8()..self.pos];
+        match t {
+            "newline" => Ok(Value::Char('\n')),
+            "return" => Ok(Value::Char('\r')),
+            "space" => Ok(Value::Char(' ')),
+            "tab" => Ok(Value::Char('\t')),
+            "formfeed" => Ok(Value::Char('\u{c}')),
+            "backspace" => Ok(Value::Char('\u{8}')),
+            _ if rest.is_empty() => Ok(Value::Char(c)),
+            _ if c == 'u' && rest.len() == 4 =>
+                u32::from_str_radix(rest, 16).ok().and_then(::std::char::from_u32).map(Value::Char)
+                .ok_or_else(|| self.error_at(start, format!("invalid char \\{}", t))),
+            _ => Err(self.error_at(start, format!("invalid char \\{}", t)))
+        }
+    }
+}
+
+fn is_symbol(t:&str) -> bool {
+    let mut cs = t.chars();
+    let ok_start = match (cs.next(), cs.next()) {
+        (None, _) => false,
+        (Some(c), _) if c.is_ascii_digit() || c == ':' || c == '#' => false,
+        (Some('+'), Some(d)) | (Some('-'), Some(d)) | (Some('.'), Some(d)) => !d.is_ascii_digit(),
+        _ => true
+    };
+    ok_start && t.chars().all(is_symbol_char) && (t == "/" || match t.find('/')
+    {None => true,
+     Some(i) => i > 0 && i + 1 < t.len() && !t[i + 1..].contains('/') && is_symbol(&t[i + 1..])})
+}
+
+fn number(t:&str) -> Result<Value,String> {
+    let digits = |s:&str| !s.is_empty() && s.bytes().all(|b| b.is_ascii_digit());
+    let body = t.trim_start_matches(['+','-']);
+    if t.len() - body.len() > 1 {return Err(format!("invalid number {}", t))}
+    let int = body.strip_suffix('N').unwrap_or(body);
+    if digits(int) {
+        let int = &t[..t.len() - (body.len() - int.len())];
+        return int.parse().map(Value::Int).map_err(|_| format!("integer {} out of range", t))
+    }
+    let float = body.strip_suffix('M').unwrap_or(body);
+    let (mantissa, exp) = match float.find(['e','E'])
+    {Some(i) => (&float[..i], Some(&float[i + 1..])), None => (float, None)};
+    let (whole, frac) = match mantissa.find('.')
+    {Some(i) => (&mantissa[..i], Some(&mantissa[i + 1..])), None => (mantissa, None)};
+    let valid = digits(whole)
+        && frac.is_none_or(|f| f.is_empty() || digits(f))
+        && exp.is_none_or(|e| digits(e.strip_prefix(['+','-']).unwrap_or(e)))
+        && (frac.is_some() || exp.is_some() || float.len() < body.len());
+    if !valid {return Err(format!("invalid number {}", t))}
+    let float = &t[..t.len() - (body.len() - float.len())];
+    match float.parse::<f64>()
+    {Ok(x) if x.is_finite() => Ok(Value::Float(x)),
+     _ => Err(format!("float {} out of range", t))}
+}
+
+fn qualify(ns:&str, k:Value) -> Value
+{match k
+ {Value::Keyword(k) => match k.namespace()
+  {None => Value::Keyword(Keyword::with_ns(ns, k.name())),
+   Some("_") => Value::Keyword(Keyword::new(k.name())),
+   Some(_) => Value::Keyword(k)},
+  Value::Symbol(s) => match s.namespace()
+  {None => Value::Symbol(Symbol::with_ns(ns, s.name())),
+   Some("_") => Value::Symbol(Symbol::new(s.name())),
+   Some(_) => Value::Symbol(s)},
+  k => k}}
+
+/// `yyyy-mm-ddThh:mm:ss`, with optional fraction and an offset `Z` or
+/// `+hh:mm`. clojure also takes prefixes of it, and so do we.
+fn is_inst(s:&str) -> bool {
+    let shape = "dddd-dd-ddTdd:dd:dd";
+    let n = s.len().min(shape.len());
+    if !s.is_char_boundary(n) {return false}
+    let (date, rest) = s.split_at(n);
+    if !date.is_ascii() || date.is_empty() || date.len() < 4
+        || !date.bytes().zip(shape.bytes()).all(|(c,p)| if p == b'd' {c.is_ascii_digit()} else {c == p})
+    {return false}
+    if rest.is_empty() {return [4, 7, 10, 13, 16, 19].contains(&date.len())}
+    if date.len() < shape.len() {return false}
+    let rest = match rest.strip_prefix('.')
+    {Some(r) => {let f = r.trim_start_matches(|c:char| c.is_ascii_digit());
+                 if f.len() == r.len() {return false} f}
+     None => rest};
+    rest == "Z" || rest == "z" || {
+        let b = rest.as_bytes();
+        b.len() == 6 && (b[0] == b'+' || b[0] == b'-') && b[3] == b':'
+            && [1, 2, 4, 5].iter().all(|&i| b[i].is_ascii_digit())
+    }
+}
+
+fn is_uuid(s:&str) -> bool
+{s.len() == 36 && s.char_indices().all(|(i,c)| if [8, 13, 18, 23].contains(&i) {c == '-'} else {c.is_ascii_hexdigit()})}
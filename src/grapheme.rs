@@ -0,0 +1,93 @@
+use grapheme_tables::{GCB,EXTENDED_PICTOGRAPHIC};
+use std::cmp::Ordering::{Less,Equal,Greater};
+
+/// the grapheme cluster break property.
+#[derive(Clone,Copy,PartialEq,Eq,Debug)]
+pub enum Gcb {Cr,Lf,Control,Extend,Zwj,RegionalIndicator,Prepend,SpacingMark,L,V,T,Lv,Lvt,Other}
+
+fn gcb(c:char) -> Gcb {
+    let n = c as u32;
+    if (0xAC00..0xD7A4).contains(&n)
+    {return if (n - 0xAC00).is_multiple_of(28) {Gcb::Lv} else {Gcb::Lvt}}
+    match GCB.binary_search_by(|&(lo,hi,_)| if hi < c {Less} else if lo > c {Greater} else {Equal})
+    {Ok(i) => GCB[i].2, Err(_) => Gcb::Other}
+}
+
+fn is_pictographic(c:char) -> bool
+{EXTENDED_PICTOGRAPHIC.binary_search_by(|&(lo,hi)| if hi < c {Less} else if lo > c {Greater} else {Equal}).is_ok()}
+
+/// whether there is a grapheme cluster boundary at byte `i` of `s`, which
+/// must be on a char boundary.
+///
+/// follows the rules of [uax #29](https://www.unicode.org/reports/tr29/) for
+/// extended grapheme clusters as of unicode 14.0. the look-behind needed for
+/// emoji zwj sequences and regional indicator pairs is done by scanning back.
+pub fn is_boundary(s:&str, i:usize) -> bool {
+    use self::Gcb::*;
+    if i == 0 || i == s.len() {return true}
+    let (x,y) = match (s[..i].chars().next_back(), s[i..].chars().next())
+    {(Some(x),Some(y)) => (x,y), _ => return true};
+    match (gcb(x),gcb(y)) {
+        (Cr,Lf) => false,
+        (Control,_) | (Cr,_) | (Lf,_) => true,
+        (_,Control) | (_,Cr) | (_,Lf) => true,
+        (L,L) | (L,V) | (L,Lv) | (L,Lvt) => false,
+        (Lv,V) | (Lv,T) | (V,V) | (V,T) => false,
+        (Lvt,T) | (T,T) => false,
+        (_,Extend) | (_,Zwj) | (_,SpacingMark) => false,
+        (Prepend,_) => false,
+        (Zwj,_) if is_pictographic(y) =>
+            !s[..i - x.len_utf8()].chars().rev()
+            .find(|&c| gcb(c) != Extend)
+            .is_some_and(is_pictographic),
+        (RegionalIndicator,RegionalIndicator) =>
+            s[..i].chars().rev()
+            .take_while(|&c| gcb(c) == RegionalIndicator)
+            .count() % 2 == 0,
+        _ => true
+    }
+}
+
+/// iterate over the extended grapheme clusters of a str, from either end.
+///
+/// # example
+/// ```
+/// use protocoll::string::graphemes;
+/// let s = "e\u{301}\u{1F1E9}\u{1F1EA}\u{1F469}\u{200D}\u{1F4BB}\r\n";
+/// assert_eq!(graphemes(s).collect::<Vec<_>>(),
+///            ["e\u{301}", "\u{1F1E9}\u{1F1EA}", "\u{1F469}\u{200D}\u{1F4BB}", "\r\n"]);
+/// assert_eq!(graphemes(s).next_back(), Some("\r\n"));
+/// ```
+pub fn graphemes(s:&str) -> Graphemes<'_>
+{Graphemes{s, front:0, back:s.len()}}
+
+#[derive(Clone)]
+pub struct Graphemes<'a> {s:&'a str, front:usize, back:usize}
+
+impl<'a> Iterator for Graphemes<'a>
+{type Item = &'a str;
+ fn next(&mut self) -> Option<&'a str>
+ {if self.front >= self.back {return None}
+  let start = self.front;
+  let end = self.s[start..self.back].char_indices().skip(1)
+      .map(|(i,_)| start + i)
+      .find(|&i| is_boundary(self.s, i))
+      .unwrap_or(self.back);
+  self.front = end; Some(&self.s[start..end])}}
+
+impl<'a> DoubleEndedIterator for Graphemes<'a>
+{fn next_back(&mut self) -> Option<&'a str>
+ {if self.front >= self.back {return None}
+  let end = self.back;
+  let start = self.s[self.front..end].char_indices().rev()
+      .map(|(i,_)| self.front + i)
+      .find(|&i| i == self.front || is_boundary(self.s, i))
+      .unwrap_or(self.front);
+  self.back = start; Some(&self.s[start..end])}}
+
+/// the byte index where the grapheme cluster at index `n` starts, or the length
+/// of `s` if there are exactly `n` clusters.
+pub fn byte_index(s:&str, n:usize) -> Option<usize>
+{let mut g = graphemes(s);
+ for _ in 0..n {g.next()?;}
+ Some(g.front)}
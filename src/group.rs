@@ -0,0 +1,85 @@
+use _map::{Map,MapMut};
+use _seq::Seq;
+use _set::Set;
+use std::mem;
+
+/// counts how many times each item occurs in `coll`.
+///
+/// like `clojure`'s [`frequencies`](http://clojuredocs.org/clojure.core/frequencies).
+///
+/// # example
+/// ```
+/// use protocoll::map::{self,VecSortedMap};
+/// use std::collections::HashMap;
+/// let m:HashMap<_,_> = map::frequencies("hello".chars());
+/// assert_eq!(m[&'l'], 2);
+/// let m:VecSortedMap<_,_> = map::count_by(|w:&&str| w.len(), "a bb cc d eee".split(' '));
+/// assert_eq!(m.view_content(), &[(1,2), (2,2), (3,1)]);
+/// ```
+pub fn frequencies<M,I>(coll:I) -> M where I:IntoIterator, M:MapMut<I::Item,usize> + Default
+{let mut m = M::default();
+ for t in coll {m.update_mut(t, 0, |n| *n += 1)}
+ m}
+
+/// counts the items of `coll` by their keys `f(&t)`.
+pub fn count_by<M,I,F,K>(mut f:F, coll:I) -> M where I:IntoIterator, F:FnMut(&I::Item) -> K, M:MapMut<K,usize> + Default
+{let mut m = M::default();
+ for t in coll {m.update_mut(f(&t), 0, |n| *n += 1)}
+ m}
+
+/// groups the items of `coll` by their keys `f(&t)`, into seqs in their
+/// order in `coll`.
+///
+/// like `clojure`'s [`group-by`](http://clojuredocs.org/clojure.core/group-by).
+///
+/// # example
+/// ```
+/// use protocoll::map;
+/// use protocoll::set::VecSortedSet;
+/// use std::collections::{BTreeMap,VecDeque};
+/// let m:BTreeMap<_,VecDeque<_>> = map::group_by(|n:&i32| n % 3, vec![5,1,4,3,2,4]);
+/// assert_eq!(m[&1], [1,4,4]);
+/// let m:BTreeMap<_,VecSortedSet<_>> = map::group_by_set(|n:&i32| n % 3, vec![5,1,4,3,2,4]);
+/// assert_eq!(m[&1].view_content(), &[1,4]);
+/// ```
+pub fn group_by<M,I,F,K,S>(mut f:F, coll:I) -> M
+    where I:IntoIterator, F:FnMut(&I::Item) -> K, M:MapMut<K,S> + Default, S:Seq<I::Item> + Default
+{let mut m = M::default();
+ for t in coll {let k = f(&t); m.update_mut(k, S::default(), |s| *s = mem::take(s).inc(t))}
+ m}
+
+/// groups the items of `coll` by their keys `f(&t)`, into sets.
+pub fn group_by_set<M,I,F,K,S>(mut f:F, coll:I) -> M
+    where I:IntoIterator, F:FnMut(&I::Item) -> K, M:MapMut<K,S> + Default, S:Set<I::Item> + Default
+{let mut m = M::default();
+ for t in coll {let k = f(&t); m.update_mut(k, S::default(), |s| *s = mem::take(s).inc(t))}
+ m}
+
+/// maps the items of `coll` by their keys `f(&t)`; the last one wins.
+///
+/// # example
+/// ```
+/// use protocoll::map;
+/// use std::collections::HashMap;
+/// let m:HashMap<_,_> = map::index_by(|&(id,_):&(u8,&str)| id, vec![(1,"a"), (2,"b"), (1,"c")]);
+/// assert_eq!(m[&1], (1,"c"));
+/// ```
+pub fn index_by<M,I,F,K>(mut f:F, coll:I) -> M where I:IntoIterator, F:FnMut(&I::Item) -> K, M:Map<K,I::Item> + Default
+{coll.into_iter().fold(M::default(), |m,t| {let k = f(&t); Map::inc(m, k, t)})}
+
+/// reduces the items of `coll` with the same key `f(&t)` by `r`, each group
+/// starting from `init`.
+///
+/// # example
+/// ```
+/// use protocoll::map;
+/// use std::collections::BTreeMap;
+/// let sales = vec![("tea", 3), ("cake", 5), ("tea", 4)];
+/// let m:BTreeMap<_,_> = map::reduce_by(|s:&(&str,i32)| s.0, |sum, s| sum + s.1, 0, sales);
+/// assert_eq!(m["tea"], 7);
+/// ```
+pub fn reduce_by<M,I,F,K,A,R>(mut f:F, mut r:R, init:A, coll:I) -> M
+    where I:IntoIterator, F:FnMut(&I::Item) -> K, R:FnMut(A,I::Item) -> A, A:Clone + Default, M:MapMut<K,A> + Default
+{let mut m = M::default();
+ for t in coll {let k = f(&t); m.update_mut(k, init.clone(), |a| *a = r(mem::take(a), t))}
+ m}
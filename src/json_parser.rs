@@ -0,0 +1,430 @@
+use json_value::Json;
+use vec_sorted_map::VecSortedMap;
+use std::collections::{HashMap,BTreeMap};
+use std::hash::BuildHasher;
+use std::str::Bytes;
+use std::error;
+use std::fmt::{self,Display,Formatter};
+
+/// an error while parsing, at a 1-based line and column counted in chars.
+#[derive(Clone,PartialEq,Eq,Debug)]
+pub struct Error {pub line:usize, pub column:usize, pub msg:String}
+
+impl Display for Error
+{fn fmt(&self, fmt:&mut Formatter) -> fmt::Result
+ {write!(fmt, "{}:{}: {}", self.line, self.column, self.msg)}}
+
+impl error::Error for Error {}
+
+/// what the parser meets, in document order.
+#[derive(Clone,PartialEq,Debug)]
+pub enum Event {
+    Null,
+    Bool(bool),
+    /// an integer which fits into an `i64`.
+    Int(i64),
+    /// a positive integer which fits into a `u64` but not an `i64`.
+    UInt(u64),
+    /// any other number.
+    Float(f64),
+    Str(String),
+    /// the key of the next value in an object.
+    Key(String),
+    StartArray,
+    EndArray,
+    StartObject,
+    EndObject,
+}
+
+impl Event {
+    fn describe(&self) -> &'static str
+    {match *self
+     {Event::Null => "null", Event::Bool(_) => "a bool",
+      Event::Int(_) | Event::UInt(_) | Event::Float(_) => "a number",
+      Event::Str(_) => "a string", Event::Key(_) => "a key",
+      Event::StartArray => "an array", Event::EndArray => "the end of an array",
+      Event::StartObject => "an object", Event::EndObject => "the end of an object"}}
+}
+
+#[derive(Clone,Copy)]
+enum Ctx {Array{first:bool}, Object{first:bool, key:bool}}
+
+/// a streaming json parser, pulling [`Event`](enum.Event.html)s from bytes.
+///
+/// the bytes can come from a str, or any iterator such as one over a
+/// `BufReader`. the document must be a single value; the parser stops at the
+/// first error. arrays and objects nested more than
+/// [`max_depth`](#method.max_depth) deep are an error too.
+///
+/// # example
+/// ```
+/// use protocoll::json::{Parser,Event};
+/// let mut p = Parser::from_str(r#"{"a": [1, -2, 18446744073709551615, 0.5]}"#);
+/// assert_eq!(p.next(), Some(Ok(Event::StartObject)));
+/// assert_eq!(p.next(), Some(Ok(Event::Key("a".to_owned()))));
+/// assert_eq!(p.next(), Some(Ok(Event::StartArray)));
+/// assert_eq!(p.next(), Some(Ok(Event::Int(1))));
+/// assert_eq!(p.next(), Some(Ok(Event::Int(-2))));
+/// assert_eq!(p.next(), Some(Ok(Event::UInt(u64::MAX))));
+/// assert_eq!(p.next(), Some(Ok(Event::Float(0.5))));
+/// assert_eq!(p.position(), (1, 37));
+/// assert_eq!(p.count(), 2);
+/// let e = Parser::from_str("[1,\n 2,]").last().unwrap().unwrap_err();
+/// assert_eq!(e.to_string(), "2:4: expected a value, found ]");
+/// ```
+pub struct Parser<I> where I:Iterator<Item = u8> {
+    bytes:I,
+    peeked:Option<u8>,
+    line:usize,
+    column:usize,
+    start:(usize,usize),
+    stack:Vec<Ctx>,
+    max_depth:usize,
+    done:bool,
+    failed:bool,
+}
+
+impl<'a> Parser<Bytes<'a>> {
+    #[allow(clippy::should_implement_trait)]
+    pub fn from_str(s:&'a str) -> Parser<Bytes<'a>>
+    {Parser::new(s.bytes())}
+}
+
+impl<I> Parser<I> where I:Iterator<Item = u8> {
+    pub fn new(bytes:I) -> Parser<I>
+    {Parser{bytes, peeked:None, line:1, column:1, start:(1,1), stack:Vec::new(), max_depth:256, done:false, failed:false}}
+
+    /// sets how many arrays and objects may be nested in one another. it is
+    /// 256 by default.
+    ///
+    /// # example
+    /// ```
+    /// use protocoll::json::{self,Json,Parser};
+    /// assert!(json::from_str::<Json>(&"[".repeat(200000)).is_err());
+    /// let mut p = Parser::from_str("[[[]]]").max_depth(2);
+    /// assert_eq!(p.nth(2).unwrap().unwrap_err().to_string(), "1:3: arrays and objects nested too deeply");
+    /// ```
+    pub fn max_depth(mut self, n:usize) -> Parser<I>
+    {self.max_depth = n; self}
+
+    /// the line and column where the last event started.
+    pub fn position(&self) -> (usize,usize)
+    {self.start}
+
+    /// an error at where the last event started.
+    pub fn error(&self, msg:String) -> Error
+    {Error{line:self.start.0, column:self.start.1, msg}}
+
+    fn error_here(&self, msg:String) -> Error
+    {Error{line:self.line, column:self.column, msg}}
+
+    fn peek(&mut self) -> Option<u8>
+    {if self.peeked.is_none() {self.peeked = self.bytes.next()} self.peeked}
+
+    fn bump(&mut self) -> Option<u8>
+    {let b = self.peek()?;
+     self.peeked = None;
+     if b == b'\n' {self.line += 1; self.column = 1}
+     else if b & 0xc0 != 0x80 {self.column += 1}
+     Some(b)}
+
+    fn skip_space(&mut self)
+    {while let Some(b' ') | Some(b'\t') | Some(b'\n') | Some(b'\r') = self.peek() {self.bump();}}
+
+    fn found(&mut self) -> String
+    {match self.peek()
+     {None => "the end".to_owned(),
+      Some(b) if b.is_ascii_graphic() => (b as char).to_string(),
+      Some(b) => format!("byte {:#04x}", b)}}
+
+    fn expect(&mut self, b:u8, what:&str) -> Result<(),Error>
+    {if self.peek() == Some(b) {self.bump(); Ok(())}
+     else {let f = self.found(); Err(self.error_here(format!("expected {}, found {}", what, f)))}}
+
+    fn step(&mut self) -> Result<Option<Event>,Error> {
+        self.skip_space();
+        self.start = (self.line, self.column);
+        match self.stack.last().cloned() {
+            None if self.done =>
+                if self.peek().is_none() {Ok(None)}
+                else {let f = self.found(); Err(self.error_here(format!("expected the end, found {}", f)))},
+            None => self.value(),
+            Some(Ctx::Array{first}) => {
+                if self.peek() == Some(b']') && first {self.bump(); return Ok(Some(self.close(Event::EndArray)))}
+                if !first {
+                    if self.peek() == Some(b']') {self.bump(); return Ok(Some(self.close(Event::EndArray)))}
+                    self.expect(b',', ", or ]")?;
+                    self.skip_space();
+                    self.start = (self.line, self.column);
+                }
+                *self.stack.last_mut().unwrap() = Ctx::Array{first:false};
+                self.value()
+            }
+            Some(Ctx::Object{first, key:true}) => {
+                if self.peek() == Some(b'}') {self.bump(); return Ok(Some(self.close(Event::EndObject)))}
+                if !first {
+                    self.expect(b',', ", or }")?;
+                    self.skip_space();
+                    self.start = (self.line, self.column);
+                }
+                if self.peek() != Some(b'"')
+                {let f = self.found(); return Err(self.error_here(format!("expected a key, found {}", f)))}
+                self.bump();
+                let k = self.string()?;
+                self.skip_space();
+                self.expect(b':', ":")?;
+                *self.stack.last_mut().unwrap() = Ctx::Object{first:false, key:false};
+                Ok(Some(Event::Key(k)))
+            }
+            Some(Ctx::Object{first, key:false}) => {
+                *self.stack.last_mut().unwrap() = Ctx::Object{first, key:true};
+                self.value()
+            }
+        }
+    }
+
+    fn close(&mut self, e:Event) -> Event
+    {self.stack.pop(); if self.stack.is_empty() {self.done = true} e}
+
+    fn scalar(&mut self, e:Event) -> Option<Event>
+    {if self.stack.is_empty() {self.done = true} Some(e)}
+
+    fn value(&mut self) -> Result<Option<Event>,Error> {
+        if (self.peek() == Some(b'{') || self.peek() == Some(b'[')) && self.stack.len() >= self.max_depth
+        {return Err(self.error_here("arrays and objects nested too deeply".to_owned()))}
+        match self.peek() {
+            Some(b'{') => {self.bump(); self.stack.push(Ctx::Object{first:true, key:true}); Ok(Some(Event::StartObject))}
+            Some(b'[') => {self.bump(); self.stack.push(Ctx::Array{first:true}); Ok(Some(Event::StartArray))}
+            Some(b'"') => {self.bump(); let s = self.string()?; Ok(self.scalar(Event::Str(s)))}
+            Some(b't') => {self.literal("true")?; Ok(self.scalar(Event::Bool(true)))}
+            Some(b'f') => {self.literal("false")?; Ok(self.scalar(Event::Bool(false)))}
+            Some(b'n') => {self.literal("null")?; Ok(self.scalar(Event::Null))}
+            Some(b'-') | Some(b'0'..=b'9') => {let n = self.number()?; Ok(self.scalar(n))}
+            None if self.stack.is_empty() && !self.done => Err(self.error_here("expected a value, found the end".to_owned())),
+            _ => {let f = self.found(); Err(self.error_here(format!("expected a value, found {}", f)))}
+        }
+    }
+
+    fn literal(&mut self, word:&str) -> Result<(),Error>
+    {for b in word.bytes()
+     {if self.peek() != Some(b) {let f = self.found(); return Err(self.error_here(format!("expected {}, found {}", word, f)))}
+      self.bump();}
+     Ok(())}
+
+    fn digits(&mut self, s:&mut String) -> Result<(),Error>
+    {if !self.peek().is_some_and(|b| b.is_ascii_digit())
+     {let f = self.found(); return Err(self.error_here(format!("expected a digit, found {}", f)))}
+     while let Some(b) = self.peek().filter(u8::is_ascii_digit) {self.bump(); s.push(b as char)}
+     Ok(())}
+
+    fn number(&mut self) -> Result<Event,Error> {
+        let mut s = String::new();
+        if self.peek() == Some(b'-') {self.bump(); s.push('-')}
+        if self.peek() == Some(b'0') {self.bump(); s.push('0')} else {self.digits(&mut s)?}
+        let mut int = true;
+        if self.peek() == Some(b'.') {self.bump(); s.push('.'); int = false; self.digits(&mut s)?}
+        if let Some(e) = self.peek().filter(|&b| b == b'e' || b == b'E') {
+            self.bump(); s.push(e as char); int = false;
+            if let Some(sign) = self.peek().filter(|&b| b == b'+' || b == b'-') {self.bump(); s.push(sign as char)}
+            self.digits(&mut s)?
+        }
+        if int {
+            if let Ok(n) = s.parse() {return Ok(Event::Int(n))}
+            if let Ok(n) = s.parse() {return Ok(Event::UInt(n))}
+        }
+        match s.parse::<f64>()
+        {Ok(x) if x.is_finite() => Ok(Event::Float(x)),
+         _ => Err(Error{line:self.start.0, column:self.start.1, msg:format!("number {} out of range", s)})}
+    }
+
+    fn hex4(&mut self) -> Result<u32,Error>
+    {let mut n = 0;
+     for _ in 0..4
+     {match self.peek().and_then(|b| (b as char).to_digit(16))
+      {Some(d) => {self.bump(); n = n * 16 + d}
+       None => {let f = self.found(); return Err(self.error_here(format!("expected a hex digit, found {}", f)))}}}
+     Ok(n)}
+
+    fn string(&mut self) -> Result<String,Error> {
+        let mut buf = Vec::new();
+        let start = (self.line, self.column - 1);
+        loop {
+            match self.peek() {
+                None => return Err(Error{line:start.0, column:start.1, msg:"unterminated string".to_owned()}),
+                Some(b'"') => {self.bump(); break}
+                Some(b) if b < 0x20 => return Err(self.error_here(format!("unescaped control character {:#04x} in string", b))),
+                Some(b'\\') => {
+                    self.bump();
+                    let c = match self.peek() {
+                        Some(b'"') => '"', Some(b'\\') => '\\', Some(b'/') => '/',
+                        Some(b'b') => '\u{8}', Some(b'f') => '\u{c}',
+                        Some(b'n') => '\n', Some(b'r') => '\r', Some(b't') => '\t',
+                        Some(b'u') => {
+                            self.bump();
+                            let hi = self.hex4()?;
+                            let n = if (0xd800..0xdc00).contains(&hi) {
+                                self.expect(b'\\', "a low surrogate")?;
+                                self.expect(b'u', "a low surrogate")?;
+                                let lo = self.hex4()?;
+                                if !(0xdc00..0xe000).contains(&lo)
+                                {return Err(self.error_here(format!("invalid low surrogate {:04x}", lo)))}
+                                0x10000 + ((hi - 0xd800) << 10) + (lo - 0xdc00)
+                            } else {hi};
+                            match ::std::char::from_u32(n)
+                            {Some(c) => {buf.extend_from_slice(c.encode_utf8(&mut [0;4]).as_bytes()); continue}
+                             None => return Err(self.error_here(format!("unpaired surrogate {:04x}", n)))}
+                        }
+                        _ => {let f = self.found(); return Err(self.error_here(format!("invalid escape \\{}", f)))}
+                    };
+                    self.bump();
+                    buf.push(c as u8)
+                }
+                Some(b) => {self.bump(); buf.push(b)}
+            }
+        }
+        String::from_utf8(buf).map_err(|_| Error{line:start.0, column:start.1, msg:"invalid utf-8 in string".to_owned()})
+    }
+
+    /// the next event, which must be there.
+    pub fn next_event(&mut self) -> Result<Event,Error>
+    {match self.next()
+     {Some(r) => r,
+      None => Err(Error{line:self.line, column:self.column, msg:"expected a value, found the end".to_owned()})}}
+}
+
+impl<I> Iterator for Parser<I> where I:Iterator<Item = u8> {
+    type Item = Result<Event,Error>;
+    fn next(&mut self) -> Option<Result<Event,Error>>
+    {if self.failed {return None}
+     match self.step()
+     {Ok(e) => e.map(Ok),
+      Err(e) => {self.failed = true; Some(Err(e))}}}
+}
+
+/// types which can be read from json [`Event`](enum.Event.html)s.
+///
+/// objects can be read into maps from `String`, arrays into `Vec`s, and
+/// anything into a [`Json`](enum.Json.html). a type mismatch is reported at
+/// the value which does not fit.
+///
+/// # example
+/// ```
+/// use protocoll::json;
+/// use protocoll::map::VecSortedMap;
+/// let m:VecSortedMap<String,Vec<u64>> = json::from_str(r#"{"b": [2], "a": [1, 0]}"#).unwrap();
+/// assert_eq!(m["a"], [1,0]);
+/// let e = json::from_str::<VecSortedMap<String,Vec<u64>>>(r#"{"a": [1, -1]}"#).unwrap_err();
+/// assert_eq!(e.to_string(), "1:11: expected an unsigned integer, found -1");
+/// ```
+///
+/// `from_event` may be called with any event, and reports the ones which do
+/// not fit instead of panicking:
+///
+/// ```
+/// use protocoll::json::{FromJson,Parser,Event,Json};
+/// use std::collections::HashMap;
+/// let mut p = Parser::from_str("1");
+/// let e = HashMap::<String,Json>::from_event(Event::StartObject, &mut p).unwrap_err();
+/// assert_eq!(e.to_string(), "1:1: expected a key, found 1");
+/// ```
+pub trait FromJson where Self:Sized {
+    /// reads `Self` starting from the event `e` just pulled from `p`.
+    fn from_event<I>(e:Event, p:&mut Parser<I>) -> Result<Self,Error> where I:Iterator<Item = u8>;
+}
+
+/// parses a whole document from `s`.
+pub fn from_str<T>(s:&str) -> Result<T,Error> where T:FromJson
+{from_bytes(s.bytes())}
+
+/// parses a whole document from `bytes`.
+pub fn from_bytes<T,I>(bytes:I) -> Result<T,Error> where T:FromJson, I:Iterator<Item = u8>
+{let mut p = Parser::new(bytes);
+ let e = p.next_event()?;
+ let t = T::from_event(e, &mut p)?;
+ match p.next() {None => Ok(t), Some(Err(e)) => Err(e), Some(Ok(_)) => Err(p.error("trailing data after value".to_owned()))}}
+
+fn mismatch<I,T>(what:&str, e:&Event, p:&Parser<I>) -> Result<T,Error> where I:Iterator<Item = u8>
+{let found = match *e
+ {Event::Int(n) => n.to_string(), Event::UInt(n) => n.to_string(), Event::Float(x) => x.to_string(),
+  ref e => e.describe().to_owned()};
+ Err(p.error(format!("expected {}, found {}", what, found)))}
+
+impl FromJson for Json {
+    fn from_event<I>(e:Event, p:&mut Parser<I>) -> Result<Json,Error> where I:Iterator<Item = u8> {
+        Ok(match e {
+            Event::Null => Json::Null,
+            Event::Bool(b) => Json::Bool(b),
+            Event::Int(n) => Json::Int(n),
+            Event::UInt(n) => Json::UInt(n),
+            Event::Float(x) => Json::Float(x),
+            Event::Str(s) => Json::Str(s),
+            Event::StartArray => Json::Array(FromJson::from_event(Event::StartArray, p)?),
+            Event::StartObject => Json::Object(FromJson::from_event(Event::StartObject, p)?),
+            ref e => return mismatch("a value", e, p)
+        })
+    }
+}
+
+impl FromJson for bool
+{fn from_event<I>(e:Event, p:&mut Parser<I>) -> Result<bool,Error> where I:Iterator<Item = u8>
+ {match e {Event::Bool(b) => Ok(b), ref e => mismatch("a bool", e, p)}}}
+
+impl FromJson for i64
+{fn from_event<I>(e:Event, p:&mut Parser<I>) -> Result<i64,Error> where I:Iterator<Item = u8>
+ {match e {Event::Int(n) => Ok(n), ref e => mismatch("an integer", e, p)}}}
+
+impl FromJson for u64
+{fn from_event<I>(e:Event, p:&mut Parser<I>) -> Result<u64,Error> where I:Iterator<Item = u8>
+ {match e
+  {Event::UInt(n) => Ok(n),
+   Event::Int(n) if n >= 0 => Ok(n as u64),
+   ref e => mismatch("an unsigned integer", e, p)}}}
+
+/// takes integers too, which may round.
+impl FromJson for f64
+{fn from_event<I>(e:Event, p:&mut Parser<I>) -> Result<f64,Error> where I:Iterator<Item = u8>
+ {match e
+  {Event::Float(x) => Ok(x),
+   Event::Int(n) => Ok(n as f64),
+   Event::UInt(n) => Ok(n as f64),
+   ref e => mismatch("a number", e, p)}}}
+
+impl FromJson for String
+{fn from_event<I>(e:Event, p:&mut Parser<I>) -> Result<String,Error> where I:Iterator<Item = u8>
+ {match e {Event::Str(s) => Ok(s), ref e => mismatch("a string", e, p)}}}
+
+/// `null` is `None`.
+impl<T> FromJson for Option<T> where T:FromJson
+{fn from_event<I>(e:Event, p:&mut Parser<I>) -> Result<Option<T>,Error> where I:Iterator<Item = u8>
+ {match e {Event::Null => Ok(None), e => T::from_event(e, p).map(Some)}}}
+
+impl<T> FromJson for Vec<T> where T:FromJson
+{fn from_event<I>(e:Event, p:&mut Parser<I>) -> Result<Vec<T>,Error> where I:Iterator<Item = u8>
+ {if e != Event::StartArray {return mismatch("an array", &e, p)}
+  let mut v = Vec::new();
+  loop
+  {match p.next_event()?
+   {Event::EndArray => return Ok(v),
+    e => v.push(T::from_event(e, p)?)}}}}
+
+fn object<I,T,F>(e:Event, p:&mut Parser<I>, mut insert:F) -> Result<(),Error>
+    where I:Iterator<Item = u8>, T:FromJson, F:FnMut(String, T)
+{if e != Event::StartObject {return mismatch("an object", &e, p)}
+ loop
+ {match p.next_event()?
+  {Event::EndObject => return Ok(()),
+   Event::Key(k) => {let e = p.next_event()?; insert(k, T::from_event(e, p)?)}
+   ref e => return mismatch("a key", e, p)}}}
+
+/// a later duplicate key wins.
+impl<T> FromJson for VecSortedMap<String,T> where T:FromJson
+{fn from_event<I>(e:Event, p:&mut Parser<I>) -> Result<VecSortedMap<String,T>,Error> where I:Iterator<Item = u8>
+ {let mut m = VecSortedMap::new(); object(e, p, |k,v| {m.insert(k,v);})?; Ok(m)}}
+
+impl<T,S> FromJson for HashMap<String,T,S> where T:FromJson, S:BuildHasher + Default
+{fn from_event<I>(e:Event, p:&mut Parser<I>) -> Result<HashMap<String,T,S>,Error> where I:Iterator<Item = u8>
+ {let mut m = HashMap::default(); object(e, p, |k,v| {m.insert(k,v);})?; Ok(m)}}
+
+impl<T> FromJson for BTreeMap<String,T> where T:FromJson
+{fn from_event<I>(e:Event, p:&mut Parser<I>) -> Result<BTreeMap<String,T>,Error> where I:Iterator<Item = u8>
+ {let mut m = BTreeMap::new(); object(e, p, |k,v| {m.insert(k,v);})?; Ok(m)}}
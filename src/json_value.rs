@@ -0,0 +1,100 @@
+use vec_sorted_map::VecSortedMap;
+use json_writer::{Writer,ToJson};
+use std::convert::TryFrom;
+use std::fmt::{self,Display,Formatter};
+
+/// dynamically typed json.
+///
+/// integers keep their precision: those which fit into an `i64` are
+/// [`Int`](#variant.Int), larger positive ones [`UInt`](#variant.UInt), and
+/// only numbers with a fraction or exponent, or beyond `u64`, are
+/// [`Float`](#variant.Float).
+///
+/// # example
+/// ```
+/// use protocoll::json::{self,Json};
+/// let j:Json = json::from_str(r#"{"id": 9007199254740993, "tags": ["a", null]}"#).unwrap();
+/// assert_eq!(j.get("id").and_then(Json::as_i64), Some(9007199254740993));
+/// assert_eq!(j.get("tags").and_then(Json::as_array).map(Vec::len), Some(2));
+/// assert_eq!(j.to_string(), r#"{"id":9007199254740993,"tags":["a",null]}"#);
+/// ```
+#[derive(Clone,PartialEq,Debug,Default)]
+pub enum Json {
+    #[default]
+    Null,
+    Bool(bool),
+    Int(i64),
+    UInt(u64),
+    Float(f64),
+    Str(String),
+    Array(Vec<Json>),
+    Object(VecSortedMap<String,Json>),
+}
+
+impl Json {
+    pub fn is_null(&self) -> bool
+    {matches!(*self, Json::Null)}
+
+    pub fn as_bool(&self) -> Option<bool>
+    {match *self {Json::Bool(b) => Some(b), _ => None}}
+
+    /// an `Int`, or a `UInt` which fits.
+    pub fn as_i64(&self) -> Option<i64>
+    {match *self {Json::Int(n) => Some(n), Json::UInt(n) => i64::try_from(n).ok(), _ => None}}
+
+    /// a `UInt`, or an `Int` which is not negative.
+    pub fn as_u64(&self) -> Option<u64>
+    {match *self {Json::UInt(n) => Some(n), Json::Int(n) => u64::try_from(n).ok(), _ => None}}
+
+    /// any number, which may round.
+    pub fn as_f64(&self) -> Option<f64>
+    {match *self
+     {Json::Float(x) => Some(x), Json::Int(n) => Some(n as f64), Json::UInt(n) => Some(n as f64),
+      _ => None}}
+
+    pub fn as_str(&self) -> Option<&str>
+    {match *self {Json::Str(ref s) => Some(s), _ => None}}
+
+    pub fn as_array(&self) -> Option<&Vec<Json>>
+    {match *self {Json::Array(ref v) => Some(v), _ => None}}
+
+    pub fn as_object(&self) -> Option<&VecSortedMap<String,Json>>
+    {match *self {Json::Object(ref m) => Some(m), _ => None}}
+
+    /// the value at `k` in an object.
+    pub fn get(&self, k:&str) -> Option<&Json>
+    {self.as_object().and_then(|m| m.get(k))}
+
+    /// the value at `i` in an array.
+    pub fn nth(&self, i:usize) -> Option<&Json>
+    {self.as_array().and_then(|v| v.get(i))}
+}
+
+impl From<bool> for Json
+{fn from(b:bool) -> Json {Json::Bool(b)}}
+
+impl From<i64> for Json
+{fn from(n:i64) -> Json {Json::Int(n)}}
+
+/// an `Int` if it fits.
+impl From<u64> for Json
+{fn from(n:u64) -> Json {i64::try_from(n).map_or(Json::UInt(n), Json::Int)}}
+
+impl From<f64> for Json
+{fn from(x:f64) -> Json {Json::Float(x)}}
+
+impl From<String> for Json
+{fn from(s:String) -> Json {Json::Str(s)}}
+
+impl From<&str> for Json
+{fn from(s:&str) -> Json {Json::Str(s.to_owned())}}
+
+impl From<Vec<Json>> for Json
+{fn from(v:Vec<Json>) -> Json {Json::Array(v)}}
+
+impl From<VecSortedMap<String,Json>> for Json
+{fn from(m:VecSortedMap<String,Json>) -> Json {Json::Object(m)}}
+
+/// prints compact json.
+impl Display for Json
+{fn fmt(&self, fmt:&mut Formatter) -> fmt::Result {self.write_json(&mut Writer::compact(fmt))}}
@@ -0,0 +1,222 @@
+use json_value::Json;
+use vec_sorted_map::VecSortedMap;
+use std::collections::{HashMap,BTreeMap,VecDeque};
+use std::hash::Hash;
+use std::fmt::{self,Write};
+
+/// a streaming json writer.
+///
+/// values are written one at a time, with the commas, and the line breaks
+/// and indentation when pretty, put in between. a value inside an object must
+/// follow its [`key`](#method.key); nothing checks that the calls make a valid
+/// document otherwise. floats which are not finite are written as `null`.
+///
+/// # example
+/// ```
+/// use protocoll::json::Writer;
+/// let mut w = Writer::pretty(String::new(), 2);
+/// w.begin_object()?;
+/// w.key("xs")?;
+/// w.begin_array()?;
+/// w.int(1)?;
+/// w.float(0.5)?;
+/// w.end_array()?;
+/// w.key("empty")?;
+/// w.begin_object()?;
+/// w.end_object()?;
+/// w.end_object()?;
+/// assert_eq!(w.into_inner(), "{\n  \"xs\": [\n    1,\n    0.5\n  ],\n  \"empty\": {}\n}");
+/// # Ok::<(), std::fmt::Error>(())
+/// ```
+pub struct Writer<W> {out:W, indent:Option<usize>, stack:Vec<bool>, after_key:bool}
+
+impl<W> Writer<W> where W:Write {
+    /// all on one line without spaces.
+    pub fn compact(out:W) -> Writer<W>
+    {Writer{out, indent:None, stack:Vec::new(), after_key:false}}
+
+    /// each item on a line of its own, indented by `indent` spaces per level.
+    pub fn pretty(out:W, indent:usize) -> Writer<W>
+    {Writer{out, indent:Some(indent), stack:Vec::new(), after_key:false}}
+
+    pub fn into_inner(self) -> W
+    {self.out}
+
+    fn newline(&mut self) -> fmt::Result
+    {match self.indent
+     {Some(n) => {self.out.write_char('\n')?;
+                  for _ in 0..n * self.stack.len() {self.out.write_char(' ')?}
+                  Ok(())}
+      None => Ok(())}}
+
+    /// the separator before an item.
+    fn item(&mut self) -> fmt::Result
+    {if self.after_key {self.after_key = false; return Ok(())}
+     match self.stack.last_mut()
+     {Some(has_items) =>
+      {let first = !*has_items;
+       *has_items = true;
+       if !first {self.out.write_char(',')?}
+       self.newline()}
+      None => Ok(())}}
+
+    fn open(&mut self, c:char) -> fmt::Result
+    {self.item()?; self.out.write_char(c)?; self.stack.push(false); Ok(())}
+
+    fn close(&mut self, c:char) -> fmt::Result
+    {if self.stack.pop().unwrap_or(false) {self.newline()?}
+     self.out.write_char(c)}
+
+    pub fn begin_array(&mut self) -> fmt::Result
+    {self.open('[')}
+
+    pub fn end_array(&mut self) -> fmt::Result
+    {self.close(']')}
+
+    pub fn begin_object(&mut self) -> fmt::Result
+    {self.open('{')}
+
+    pub fn end_object(&mut self) -> fmt::Result
+    {self.close('}')}
+
+    pub fn key(&mut self, k:&str) -> fmt::Result
+    {self.item()?;
+     write_str(&mut self.out, k)?;
+     self.out.write_str(if self.indent.is_some() {": "} else {":"})?;
+     self.after_key = true; Ok(())}
+
+    pub fn null(&mut self) -> fmt::Result
+    {self.item()?; self.out.write_str("null")}
+
+    pub fn bool(&mut self, b:bool) -> fmt::Result
+    {self.item()?; write!(self.out, "{}", b)}
+
+    pub fn int(&mut self, n:i64) -> fmt::Result
+    {self.item()?; write!(self.out, "{}", n)}
+
+    pub fn uint(&mut self, n:u64) -> fmt::Result
+    {self.item()?; write!(self.out, "{}", n)}
+
+    /// as short as reads back exactly.
+    pub fn float(&mut self, x:f64) -> fmt::Result
+    {if !x.is_finite() {return self.null()}
+     self.item()?; write!(self.out, "{:?}", x)}
+
+    pub fn string(&mut self, s:&str) -> fmt::Result
+    {self.item()?; write_str(&mut self.out, s)}
+}
+
+fn write_str<W>(w:&mut W, s:&str) -> fmt::Result where W:Write {
+    w.write_char('"')?;
+    for c in s.chars() {
+        match c {
+            '"' => w.write_str("\\\"")?,
+            '\\' => w.write_str("\\\\")?,
+            '\n' => w.write_str("\\n")?,
+            '\r' => w.write_str("\\r")?,
+            '\t' => w.write_str("\\t")?,
+            c if (c as u32) < 0x20 => write!(w, "\\u{:04x}", c as u32)?,
+            c => w.write_char(c)?
+        }
+    }
+    w.write_char('"')
+}
+
+/// types which can be written as json.
+///
+/// seqs are written as arrays, and maps with keys which are strings as
+/// objects. `None` and `()` are `null`.
+///
+/// # example
+/// ```
+/// use protocoll::Map;
+/// use protocoll::map::VecSortedMap;
+/// use protocoll::json::{self,ToJson,Json};
+/// let m = VecSortedMap::new().inc("b".to_owned(), vec![u64::MAX]).inc("a".to_owned(), vec![]);
+/// let s = json::to_string(&m);
+/// assert_eq!(s, r#"{"a":[],"b":[18446744073709551615]}"#);
+/// assert_eq!(json::from_str::<VecSortedMap<String,Vec<u64>>>(&s).unwrap(), m);
+/// assert_eq!(json::to_string_pretty(&m), "{\n  \"a\": [],\n  \"b\": [\n    18446744073709551615\n  ]\n}");
+/// ```
+pub trait ToJson {
+    fn write_json<W>(&self, w:&mut Writer<W>) -> fmt::Result where W:Write;
+}
+
+/// writes compact json.
+pub fn to_string<T>(t:&T) -> String where T:ToJson + ?Sized
+{let mut w = Writer::compact(String::new()); t.write_json(&mut w).unwrap(); w.into_inner()}
+
+/// writes pretty json, indented by two spaces.
+pub fn to_string_pretty<T>(t:&T) -> String where T:ToJson + ?Sized
+{let mut w = Writer::pretty(String::new(), 2); t.write_json(&mut w).unwrap(); w.into_inner()}
+
+impl ToJson for bool {fn write_json<W:Write>(&self, w:&mut Writer<W>) -> fmt::Result {w.bool(*self)}}
+impl ToJson for i8 {fn write_json<W:Write>(&self, w:&mut Writer<W>) -> fmt::Result {w.int(*self as i64)}}
+impl ToJson for i16 {fn write_json<W:Write>(&self, w:&mut Writer<W>) -> fmt::Result {w.int(*self as i64)}}
+impl ToJson for i32 {fn write_json<W:Write>(&self, w:&mut Writer<W>) -> fmt::Result {w.int(*self as i64)}}
+impl ToJson for i64 {fn write_json<W:Write>(&self, w:&mut Writer<W>) -> fmt::Result {w.int(*self)}}
+impl ToJson for isize {fn write_json<W:Write>(&self, w:&mut Writer<W>) -> fmt::Result {w.int(*self as i64)}}
+impl ToJson for u8 {fn write_json<W:Write>(&self, w:&mut Writer<W>) -> fmt::Result {w.uint(*self as u64)}}
+impl ToJson for u16 {fn write_json<W:Write>(&self, w:&mut Writer<W>) -> fmt::Result {w.uint(*self as u64)}}
+impl ToJson for u32 {fn write_json<W:Write>(&self, w:&mut Writer<W>) -> fmt::Result {w.uint(*self as u64)}}
+impl ToJson for u64 {fn write_json<W:Write>(&self, w:&mut Writer<W>) -> fmt::Result {w.uint(*self)}}
+impl ToJson for usize {fn write_json<W:Write>(&self, w:&mut Writer<W>) -> fmt::Result {w.uint(*self as u64)}}
+impl ToJson for f32 {fn write_json<W:Write>(&self, w:&mut Writer<W>) -> fmt::Result {w.float(*self as f64)}}
+impl ToJson for f64 {fn write_json<W:Write>(&self, w:&mut Writer<W>) -> fmt::Result {w.float(*self)}}
+impl ToJson for str {fn write_json<W:Write>(&self, w:&mut Writer<W>) -> fmt::Result {w.string(self)}}
+impl ToJson for String {fn write_json<W:Write>(&self, w:&mut Writer<W>) -> fmt::Result {w.string(self)}}
+impl ToJson for () {fn write_json<W:Write>(&self, w:&mut Writer<W>) -> fmt::Result {w.null()}}
+
+impl<T> ToJson for &T where T:ToJson + ?Sized
+{fn write_json<W:Write>(&self, w:&mut Writer<W>) -> fmt::Result {(**self).write_json(w)}}
+
+impl<T> ToJson for Box<T> where T:ToJson + ?Sized
+{fn write_json<W:Write>(&self, w:&mut Writer<W>) -> fmt::Result {(**self).write_json(w)}}
+
+impl<T> ToJson for Option<T> where T:ToJson
+{fn write_json<W:Write>(&self, w:&mut Writer<W>) -> fmt::Result
+ {match *self {Some(ref t) => t.write_json(w), None => w.null()}}}
+
+fn write_array<'a,W,T,I>(w:&mut Writer<W>, items:I) -> fmt::Result
+    where W:Write, T:ToJson + 'a, I:IntoIterator<Item = &'a T>
+{w.begin_array()?; for t in items {t.write_json(w)?} w.end_array()}
+
+fn write_object<'a,W,K,V,I>(w:&mut Writer<W>, entries:I) -> fmt::Result
+    where W:Write, K:AsRef<str> + 'a, V:ToJson + 'a, I:IntoIterator<Item = (&'a K, &'a V)>
+{w.begin_object()?; for (k,v) in entries {w.key(k.as_ref())?; v.write_json(w)?} w.end_object()}
+
+impl<T> ToJson for [T] where T:ToJson
+{fn write_json<W:Write>(&self, w:&mut Writer<W>) -> fmt::Result {write_array(w, self)}}
+
+impl<T, const N:usize> ToJson for [T;N] where T:ToJson
+{fn write_json<W:Write>(&self, w:&mut Writer<W>) -> fmt::Result {write_array(w, self)}}
+
+impl<T> ToJson for Vec<T> where T:ToJson
+{fn write_json<W:Write>(&self, w:&mut Writer<W>) -> fmt::Result {write_array(w, self)}}
+
+impl<T> ToJson for VecDeque<T> where T:ToJson
+{fn write_json<W:Write>(&self, w:&mut Writer<W>) -> fmt::Result {write_array(w, self)}}
+
+impl<K,V> ToJson for VecSortedMap<K,V> where K:AsRef<str>, V:ToJson
+{fn write_json<W:Write>(&self, w:&mut Writer<W>) -> fmt::Result {write_object(w, self.iter().map(|(k,v)| (k,v)))}}
+
+impl<K,V,S> ToJson for HashMap<K,V,S> where K:AsRef<str> + Hash + Eq, V:ToJson
+{fn write_json<W:Write>(&self, w:&mut Writer<W>) -> fmt::Result {write_object(w, self)}}
+
+impl<K,V> ToJson for BTreeMap<K,V> where K:AsRef<str>, V:ToJson
+{fn write_json<W:Write>(&self, w:&mut Writer<W>) -> fmt::Result {write_object(w, self)}}
+
+impl ToJson for Json {
+    fn write_json<W:Write>(&self, w:&mut Writer<W>) -> fmt::Result {
+        match *self {
+            Json::Null => w.null(),
+            Json::Bool(b) => w.bool(b),
+            Json::Int(n) => w.int(n),
+            Json::UInt(n) => w.uint(n),
+            Json::Float(x) => w.float(x),
+            Json::Str(ref s) => w.string(s),
+            Json::Array(ref v) => v.write_json(w),
+            Json::Object(ref m) => m.write_json(w),
+        }
+    }
+}
@@ -0,0 +1,188 @@
+use std::collections::HashMap;
+use std::sync::{Mutex,OnceLock};
+use std::hash::{Hash,Hasher};
+use std::cmp::Ordering;
+use std::ptr;
+use std::fmt::{self,Debug,Display,Formatter};
+
+/// an interned name, never freed, and only ever compared by address.
+struct Name {ns:Option<&'static str>, name:&'static str}
+
+impl PartialEq for Name
+{fn eq(&self, other:&Name) -> bool {ptr::eq(self, other)}}
+
+impl Eq for Name {}
+
+impl Hash for Name
+{fn hash<H:Hasher>(&self, state:&mut H) {(self as *const Name).hash(state)}}
+
+impl PartialOrd for Name
+{fn partial_cmp(&self, other:&Name) -> Option<Ordering> {Some(self.cmp(other))}}
+
+/// unqualified names come first, then by namespace, then by name.
+impl Ord for Name
+{fn cmp(&self, other:&Name) -> Ordering
+ {if ptr::eq(self, other) {Ordering::Equal}
+  else {(self.ns, self.name).cmp(&(other.ns, other.name))}}}
+
+#[derive(Default)]
+struct Interner
+{plain:HashMap<&'static str, &'static Name>,
+ qualified:HashMap<&'static str, HashMap<&'static str, &'static Name>>}
+
+fn leak(s:&str) -> &'static str
+{Box::leak(s.to_owned().into_boxed_str())}
+
+fn with_interner<F,R>(f:F) -> R where F:FnOnce(&mut Interner) -> R
+{static INTERNER:OnceLock<Mutex<Interner>> = OnceLock::new();
+ let mut i = INTERNER.get_or_init(Default::default).lock().unwrap_or_else(|e| e.into_inner());
+ f(&mut i)}
+
+fn intern(ns:Option<&str>, name:&str) -> &'static Name {
+    with_interner(|i| match ns {
+        None => match i.plain.get(name) {
+            Some(&n) => n,
+            None => {
+                let name = leak(name);
+                let n:&Name = Box::leak(Box::new(Name{ns:None, name}));
+                i.plain.insert(name, n); n
+            }
+        },
+        Some(ns) => {
+            if let Some(&n) = i.qualified.get(ns).and_then(|m| m.get(name)) {return n}
+            let ns = match i.qualified.get_key_value(ns) {Some((&k,_)) => k, None => leak(ns)};
+            let name = leak(name);
+            let n:&Name = Box::leak(Box::new(Name{ns:Some(ns), name}));
+            i.qualified.entry(ns).or_default().insert(name, n); n
+        }
+    })
+}
+
+fn find(ns:Option<&str>, name:&str) -> Option<&'static Name>
+{with_interner(|i| match ns
+ {None => i.plain.get(name).cloned(),
+  Some(ns) => i.qualified.get(ns).and_then(|m| m.get(name)).cloned()})}
+
+/// splits `ns/name` at the first slash, except for `/` itself.
+fn split(s:&str) -> (Option<&str>, &str)
+{match s.find('/') {Some(i) if s != "/" => (Some(&s[..i]), &s[i + 1..]), _ => (None, s)}}
+
+/// an interned keyword, as in `:name` or `:ns/name`.
+///
+/// keywords are interned in a global thread-safe table and never freed, so
+/// a keyword is a single pointer, cheap to copy, and compares for equality
+/// and hashes by identity in O(1). the hash therefore differs between runs.
+/// the order however compares the names, so a
+/// [`VecSortedMap`](../map/struct.VecSortedMap.html) keyed by keywords is
+/// sorted the same way every time, and a binary search only compares strings
+/// where it passes over different keys. look up by a keyword made with
+/// [`new`](#method.new), or with [`find`](#method.find) to avoid interning
+/// names which are not there.
+///
+/// # example
+/// ```
+/// use protocoll::Map;
+/// use protocoll::map::VecSortedMap;
+/// use protocoll::string::Keyword;
+/// let id = Keyword::new("id");
+/// assert_eq!(id, Keyword::parse("id"));
+/// assert_eq!(Keyword::with_ns("user", "id").to_string(), ":user/id");
+/// assert_eq!(Keyword::parse("user/id").namespace(), Some("user"));
+/// let m = VecSortedMap::new().inc(id, 1).inc(Keyword::new("name"), 2);
+/// assert_eq!(m[&Keyword::new("id")], 1);
+/// assert!(Keyword::find("no-such-key-anywhere").is_none());
+/// ```
+#[derive(Clone,Copy,PartialEq,Eq,Hash,PartialOrd,Ord)]
+pub struct Keyword(&'static Name);
+
+impl Keyword {
+    /// the keyword `:name`.
+    pub fn new(name:&str) -> Keyword
+    {Keyword(intern(None, name))}
+
+    /// the keyword `:ns/name`.
+    pub fn with_ns(ns:&str, name:&str) -> Keyword
+    {Keyword(intern(Some(ns), name))}
+
+    /// the keyword for `ns/name` or `name`, without the leading colon.
+    pub fn parse(s:&str) -> Keyword
+    {let (ns,name) = split(s); Keyword(intern(ns, name))}
+
+    /// the keyword for `s`, as in [`parse`](#method.parse), if it has been
+    /// interned before.
+    pub fn find(s:&str) -> Option<Keyword>
+    {let (ns,name) = split(s); find(ns, name).map(Keyword)}
+
+    pub fn name(&self) -> &'static str
+    {self.0.name}
+
+    pub fn namespace(&self) -> Option<&'static str>
+    {self.0.ns}
+
+    /// the symbol with the same namespace and name.
+    pub fn to_symbol(&self) -> Symbol
+    {Symbol(self.0)}
+}
+
+impl Display for Keyword
+{fn fmt(&self, fmt:&mut Formatter) -> fmt::Result
+ {match self.0.ns
+  {Some(ns) => write!(fmt, ":{}/{}", ns, self.0.name),
+   None => write!(fmt, ":{}", self.0.name)}}}
+
+impl Debug for Keyword
+{fn fmt(&self, fmt:&mut Formatter) -> fmt::Result {Display::fmt(self, fmt)}}
+
+/// an interned symbol, as in `name` or `ns/name`.
+///
+/// shares the interned names with [`Keyword`](struct.Keyword.html), and
+/// behaves the same way except for printing without the colon.
+///
+/// # example
+/// ```
+/// use protocoll::string::{Keyword,Symbol};
+/// let s = Symbol::parse("clojure.core/inc");
+/// assert_eq!(s.to_string(), "clojure.core/inc");
+/// assert_eq!(s.name(), "inc");
+/// assert_eq!(s.to_keyword(), Keyword::with_ns("clojure.core", "inc"));
+/// ```
+#[derive(Clone,Copy,PartialEq,Eq,Hash,PartialOrd,Ord)]
+pub struct Symbol(&'static Name);
+
+impl Symbol {
+    /// the symbol `name`.
+    pub fn new(name:&str) -> Symbol
+    {Symbol(intern(None, name))}
+
+    /// the symbol `ns/name`.
+    pub fn with_ns(ns:&str, name:&str) -> Symbol
+    {Symbol(intern(Some(ns), name))}
+
+    /// the symbol for `ns/name` or `name`.
+    pub fn parse(s:&str) -> Symbol
+    {let (ns,name) = split(s); Symbol(intern(ns, name))}
+
+    /// the symbol for `s`, as in [`parse`](#method.parse), if it has been
+    /// interned before.
+    pub fn find(s:&str) -> Option<Symbol>
+    {let (ns,name) = split(s); find(ns, name).map(Symbol)}
+
+    pub fn name(&self) -> &'static str
+    {self.0.name}
+
+    pub fn namespace(&self) -> Option<&'static str>
+    {self.0.ns}
+
+    /// the keyword with the same namespace and name.
+    pub fn to_keyword(&self) -> Keyword
+    {Keyword(self.0)}
+}
+
+impl Display for Symbol
+{fn fmt(&self, fmt:&mut Formatter) -> fmt::Result
+ {match self.0.ns
+  {Some(ns) => write!(fmt, "{}/{}", ns, self.0.name),
+   None => fmt.write_str(self.0.name)}}}
+
+impl Debug for Symbol
+{fn fmt(&self, fmt:&mut Formatter) -> fmt::Result {Display::fmt(self, fmt)}}
@@ -0,0 +1,324 @@
+use _seq::Seq;
+use std::rc::Rc;
+use std::cell::{OnceCell,RefCell};
+use std::iter::{self,FromIterator};
+use std::fmt::{Debug,Formatter,Result};
+
+/// how many items are realized at once, unless asked otherwise.
+pub const CHUNK:usize = 32;
+
+struct Source<T> {items:RefCell<Box<dyn Iterator<Item = T>>>, chunk:usize}
+
+/// a realized chunk with the one after it, or `None` at the end.
+type Realized<T> = Option<(Vec<T>, Rc<Chunk<T>>)>;
+
+/// a chunk, realized from its source on demand.
+struct Chunk<T> {items:OnceCell<Realized<T>>, src:Rc<Source<T>>}
+
+impl<T> Chunk<T> {
+    fn new(src:Rc<Source<T>>) -> Rc<Chunk<T>>
+    {Rc::new(Chunk{items:OnceCell::new(), src})}
+
+    /// a chunk realized already as `items` followed by `next`.
+    fn realized(items:Vec<T>, next:Rc<Chunk<T>>) -> Rc<Chunk<T>>
+    {let src = next.src.clone();
+     Rc::new(Chunk{items:OnceCell::from(Some((items, next))), src})}
+
+    fn realize(&self) -> Option<&(Vec<T>, Rc<Chunk<T>>)>
+    {self.items.get_or_init(|| {let items:Vec<T> = self.src.items.borrow_mut().by_ref().take(self.src.chunk).collect();
+                                if items.is_empty() {None} else {Some((items, Chunk::new(self.src.clone())))}})
+     .as_ref()}
+}
+
+impl<T> Drop for Chunk<T> {
+    /// drops the unshared chunks iteratively, so that long seqs do not
+    /// overflow the stack.
+    fn drop(&mut self) {
+        let mut next = self.items.take().and_then(|c| c).map(|(_,rc)| rc);
+        while let Some(rc) = next {
+            next = match Rc::try_unwrap(rc) {Ok(mut c) => c.items.take().and_then(|c| c).map(|(_,rc)| rc), Err(_) => None}
+        }
+    }
+}
+
+/// a lazy seq, which realizes its items on demand, a chunk at a time, and
+/// keeps them. cloning is O(1) and the clones share the realized items, so
+/// they all see the same ones, even from [`repeatedly`](fn.repeatedly.html).
+///
+/// as a [`Seq`](../trait.Seq.html) it behaves like `clojure`'s `LazySeq`:
+/// `inc` adds to the left, like `cons`, and `dec` removes from the left, like
+/// `rest`. `fun` realizes the seq up to the index.
+///
+/// # example
+/// ```
+/// use protocoll::Seq;
+/// use protocoll::seq::{self,LazySeq};
+/// let fib = seq::iterate((0u64,1u64), |&(a,b)| (b, a + b));
+/// assert_eq!(fib.get(50).map(|p| p.0), Some(12586269025));
+/// let s = seq::range(0, 10, 3).inc(-1);
+/// assert_eq!(s.iter().cloned().collect::<Vec<_>>(), [-1,0,3,6,9]);
+/// assert_eq!(s.fun()(2), Some(&3));
+/// let sums:LazySeq<i32> = seq::concat(vec![vec![1,2], vec![], vec![3]]).dec();
+/// assert_eq!(sums.iter().sum::<i32>(), 5);
+/// ```
+pub struct LazySeq<T> {head:Rc<Chunk<T>>, skip:usize}
+
+impl<T> LazySeq<T> {
+    /// a seq of the items of `coll`, realized [`CHUNK`](constant.CHUNK.html)
+    /// at a time.
+    pub fn new<I>(coll:I) -> Self where I:IntoIterator<Item = T>, I::IntoIter:'static
+    {LazySeq::chunked(CHUNK, coll)}
+
+    /// a seq of the items of `coll`, realized `n` at a time. panics if `n` is
+    /// 0.
+    pub fn chunked<I>(n:usize, coll:I) -> Self where I:IntoIterator<Item = T>, I::IntoIter:'static
+    {assert!(n > 0, "chunks need a positive size");
+     let src = Rc::new(Source{items:RefCell::new(Box::new(coll.into_iter())), chunk:n});
+     LazySeq{head:Chunk::new(src), skip:0}}
+
+    /// the item at `i`, realizing the seq up to it.
+    pub fn get(&self, i:usize) -> Option<&T>
+    {self.iter().nth(i)}
+
+    pub fn first(&self) -> Option<&T>
+    {self.get(0)}
+
+    /// realizes the first item.
+    pub fn is_empty(&self) -> bool
+    {self.first().is_none()}
+
+    /// realizes the whole seq, which never returns if it is infinite.
+    pub fn len(&self) -> usize
+    {self.iter().count()}
+
+    /// how many items are realized already, without realizing any.
+    pub fn realized(&self) -> usize
+    {let mut n = 0;
+     let mut c = &self.head;
+     while let Some(&Some((ref items, ref next))) = c.items.get() {n += items.len(); c = next}
+     n.saturating_sub(self.skip)}
+
+    /// iterates by reference, realizing items as it goes.
+    pub fn iter(&self) -> Iter<'_,T>
+    {Iter{chunk:&self.head, i:self.skip}}
+
+    /// the seq without its first item, sharing the rest.
+    ///
+    /// like `clojure`'s [`rest`](http://clojuredocs.org/clojure.core/rest).
+    pub fn rest(&self) -> Self
+    {let (mut head, mut skip) = (self.head.clone(), self.skip + 1);
+     if let Some((items, next)) = self.head.realize()
+     {if skip >= items.len() {head = next.clone(); skip = 0}}
+     else {skip = 0}
+     LazySeq{head, skip}}
+}
+
+impl<T> LazySeq<T> where T:Clone + 'static {
+    /// the first `n` items, lazily.
+    pub fn take(&self, n:usize) -> Self
+    {LazySeq::chunked(self.head.src.chunk, self.clone().into_iter().take(n))}
+}
+
+impl<T> Clone for LazySeq<T>
+{fn clone(&self) -> Self
+ {LazySeq{head:self.head.clone(), skip:self.skip}}}
+
+impl<T> Default for LazySeq<T> where T:'static
+{fn default() -> Self {LazySeq::new(iter::empty())}}
+
+/// prints only the realized items, and `..` if there may be more.
+impl<T> Debug for LazySeq<T> where T:Debug
+{fn fmt(&self, fmt:&mut Formatter) -> Result
+ {let mut l = fmt.debug_list();
+  let mut c = &self.head;
+  let mut skip = self.skip;
+  loop {match c.items.get()
+        {Some(&Some((ref items, ref next))) => {l.entries(&items[skip..]); skip = 0; c = next}
+         Some(&None) => return l.finish(),
+         None => return l.finish_non_exhaustive()}}}}
+
+pub struct Iter<'a,T:'a> {chunk:&'a Chunk<T>, i:usize}
+
+impl<'a,T> Iterator for Iter<'a,T>
+{type Item = &'a T;
+ fn next(&mut self) -> Option<&'a T>
+ {loop {let (items, next) = self.chunk.realize()?;
+        if self.i < items.len() {self.i += 1; return Some(&items[self.i - 1])}
+        self.chunk = next; self.i = 0}}}
+
+impl<'a,T:'a> IntoIterator for &'a LazySeq<T>
+{type Item = &'a T; type IntoIter = Iter<'a,T>;
+ fn into_iter(self) -> Iter<'a,T> {self.iter()}}
+
+/// clones the items, which may be shared.
+pub struct IntoIter<T>(LazySeq<T>);
+
+impl<T> Iterator for IntoIter<T> where T:Clone
+{type Item = T;
+ fn next(&mut self) -> Option<T>
+ {let t = self.0.first().cloned()?;
+  self.0 = self.0.rest();
+  Some(t)}}
+
+impl<T> IntoIterator for LazySeq<T> where T:Clone
+{type Item = T; type IntoIter = IntoIter<T>;
+ fn into_iter(self) -> IntoIter<T> {IntoIter(self)}}
+
+/// collects `iter` eagerly, as it may borrow; use [`new`](#method.new) for a
+/// lazy one.
+impl<T> FromIterator<T> for LazySeq<T> where T:'static
+{fn from_iter<I>(iter:I) -> LazySeq<T> where I:IntoIterator<Item = T>
+ {LazySeq::new(iter.into_iter().collect::<Vec<_>>())}}
+
+impl<T> Seq<T> for LazySeq<T> where T:Clone + 'static {
+    /// O(n), realizing the seq up to `i`.
+    fn fun<'a>(&'a self) -> Box<dyn Fn(usize) -> Option<&'a T> + 'a>
+    {Box::new(move |i| self.get(i))}
+
+    /// like `clojure`'s [`cons`](http://clojuredocs.org/clojure.core/cons).
+    /// the item is realized at once, in front of the shared rest.
+    ///
+    /// # example
+    /// ```
+    /// use protocoll::Seq;
+    /// use protocoll::seq::LazySeq;
+    /// let s = LazySeq::new(vec![-1]).dec().plus(0..100_000);
+    /// assert_eq!(s.first(), Some(&99_999));
+    /// assert_eq!(s.into_iter().count(), 100_000);
+    /// ```
+    fn inc(self, i:T) -> Self
+    {let (items, next) = match self.head.items.get()
+     {Some(&Some((ref items, ref next))) if self.skip > 0 =>
+      (iter::once(i).chain(items[self.skip..].iter().cloned()).collect(), next.clone()),
+      _ => (vec![i], self.head.clone())};
+     LazySeq{head:Chunk::realized(items, next), skip:0}}
+
+    fn dec(self) -> Self
+    {self.rest()}
+
+    fn zero(self) -> Self
+    {LazySeq::default()}
+
+    fn shrink(self) -> Self
+    {self}
+}
+
+/// `x`, `f(x)`, `f(f(x))`, and so on.
+///
+/// like `clojure`'s [`iterate`](http://clojuredocs.org/clojure.core/iterate).
+pub fn iterate<T,F>(x:T, mut f:F) -> LazySeq<T> where T:'static, F:FnMut(&T) -> T + 'static
+{LazySeq::new(iter::successors(Some(x), move |x| Some(f(x))))}
+
+/// `x` forever.
+///
+/// like `clojure`'s [`repeat`](http://clojuredocs.org/clojure.core/repeat).
+pub fn repeat<T>(x:T) -> LazySeq<T> where T:Clone + 'static
+{LazySeq::new(iter::repeat(x))}
+
+/// the results of calling `f` forever, which are kept once realized.
+///
+/// like `clojure`'s [`repeatedly`](http://clojuredocs.org/clojure.core/repeatedly).
+///
+/// # example
+/// ```
+/// use protocoll::seq;
+/// let mut n = 0;
+/// let s = seq::repeatedly(move || {n += 1; n});
+/// let t = s.clone();
+/// assert_eq!(s.get(40), Some(&41));
+/// assert_eq!(t.realized(), 64);
+/// assert_eq!(t.iter().take(3).cloned().collect::<Vec<_>>(), [1,2,3]);
+/// ```
+pub fn repeatedly<T,F>(f:F) -> LazySeq<T> where T:'static, F:FnMut() -> T + 'static
+{LazySeq::new(iter::repeat_with(f))}
+
+/// the items of `coll` over and over, or nothing if it is empty. the items
+/// are kept as they are first pulled, and replayed once `coll` runs out, so
+/// an infinite `coll` is just its own items.
+///
+/// like `clojure`'s [`cycle`](http://clojuredocs.org/clojure.core/cycle).
+///
+/// # example
+/// ```
+/// use protocoll::seq;
+/// assert_eq!(seq::cycle(vec![1,2,3]).take(7).iter().sum::<i32>(), 13);
+/// assert_eq!(seq::cycle(0..).get(100), Some(&100));
+/// assert!(seq::cycle(Vec::<i32>::new()).is_empty());
+/// ```
+pub fn cycle<I>(coll:I) -> LazySeq<I::Item> where I:IntoIterator, I::IntoIter:'static, I::Item:Clone + 'static
+{let mut items = coll.into_iter().fuse();
+ let (mut seen, mut i) = (Vec::new(), 0);
+ LazySeq::new(iter::from_fn(move || match items.next()
+                            {Some(t) => {seen.push(t.clone()); Some(t)}
+                             None if seen.is_empty() => None,
+                             None => {i %= seen.len(); i += 1; Some(seen[i - 1].clone())}}))}
+
+/// numbers which can be added without overflowing, for
+/// [`range`](fn.range.html) and [`range_from`](fn.range_from.html).
+pub trait CheckedAdd where Self:Sized {
+    /// the sum, or `None` if it overflows.
+    fn checked_add(self, other:Self) -> Option<Self>;
+}
+
+macro_rules! checked_add_int {
+    ($($t:ty)*) => {$(
+        impl CheckedAdd for $t
+        {fn checked_add(self, other:$t) -> Option<$t> {<$t>::checked_add(self, other)}}
+    )*}
+}
+
+checked_add_int!(i8 i16 i32 i64 i128 isize u8 u16 u32 u64 u128 usize);
+
+/// floats never overflow, they become infinite.
+impl CheckedAdd for f32
+{fn checked_add(self, other:f32) -> Option<f32> {Some(self + other)}}
+
+/// floats never overflow, they become infinite.
+impl CheckedAdd for f64
+{fn checked_add(self, other:f64) -> Option<f64> {Some(self + other)}}
+
+/// from `start` by `step` up to but excluding `end`, or down to it if `step`
+/// is negative. forever if `step` is zero. it stops early where adding `step`
+/// overflows.
+///
+/// like `clojure`'s [`range`](http://clojuredocs.org/clojure.core/range).
+///
+/// # example
+/// ```
+/// use protocoll::seq;
+/// assert_eq!(seq::range(0u8, 255, 100).iter().cloned().collect::<Vec<_>>(), [0,100,200]);
+/// assert_eq!(seq::range(5i8, -128, -60).iter().cloned().collect::<Vec<_>>(), [5,-55,-115]);
+/// assert_eq!(seq::range_from(250u8, 2).iter().count(), 3);
+/// ```
+pub fn range<T>(start:T, end:T, step:T) -> LazySeq<T> where T:Copy + PartialOrd + CheckedAdd + Default + 'static
+{let up = step > T::default();
+ LazySeq::new(iter::successors(Some(start), move |&x| x.checked_add(step))
+              .take_while(move |&x| if up {x < end} else {x > end || step == T::default()}))}
+
+/// from `start` by `step` forever, or until adding `step` overflows.
+pub fn range_from<T>(start:T, step:T) -> LazySeq<T> where T:Copy + CheckedAdd + 'static
+{LazySeq::new(iter::successors(Some(start), move |&x| x.checked_add(step)))}
+
+/// the items of each of `colls` in turn.
+///
+/// like `clojure`'s [`concat`](http://clojuredocs.org/clojure.core/concat).
+pub fn concat<I>(colls:I) -> LazySeq<<I::Item as IntoIterator>::Item>
+    where I:IntoIterator, I::IntoIter:'static, I::Item:IntoIterator, <I::Item as IntoIterator>::IntoIter:'static
+{LazySeq::new(colls.into_iter().flatten())}
+
+/// the items of the collections made by each of `fs` in turn, where each is
+/// only called when the seq gets to it. unlike the others, this seq is
+/// realized one item at a time, so that no collection is made ahead.
+///
+/// like `clojure`'s [`lazy-cat`](http://clojuredocs.org/clojure.core/lazy-cat).
+///
+/// # example
+/// ```
+/// use protocoll::seq;
+/// let s = seq::lazy_cat(vec![Box::new(|| vec![1, 2]) as Box<FnOnce() -> Vec<i32>>,
+///                            Box::new(|| panic!("never realized"))]);
+/// assert_eq!(s.take(2).iter().sum::<i32>(), 3);
+/// ```
+pub fn lazy_cat<I,F,C>(fs:I) -> LazySeq<C::Item>
+    where I:IntoIterator<Item = F>, I::IntoIter:'static, F:FnOnce() -> C, C:IntoIterator + 'static, C::IntoIter:'static
+{LazySeq::chunked(1, fs.into_iter().flat_map(|f| f()))}
@@ -3,10 +3,18 @@
 
 mod _map;
 mod vec_sorted_map;
+mod priority_map;
+mod group;
+mod submap;
+mod deep_merge;
 
 pub use _map::{Map,MapMut};
 pub mod map {
     pub use vec_sorted_map::VecSortedMap;
+    pub use priority_map::PriorityMap;
+    pub use group::{frequencies,count_by,group_by,group_by_set,index_by,reduce_by};
+    pub use submap::{SubMap,keep_first,keep_last,map_keys,map_vals,invert,invert_multi,zipmap};
+    pub use deep_merge::{DeepMerge,MergePolicy,SeqPolicy,NilPolicy};
 }
 
 mod _set;
@@ -17,7 +25,81 @@ pub mod set {
 }
 
 mod _seq;
-pub use _seq::Seq;
+mod transducer;
+mod parallel;
+mod _deque;
+mod persistent_deque;
+mod lazy_seq;
+mod partition;
+mod _heap;
+mod min_heap;
+pub use _seq::{Seq,Array,Vector};
+pub use _deque::Deque;
+pub use _heap::Heap;
+pub mod seq {
+    pub use _seq::OutOfBounds;
+    pub use persistent_deque::PersistentDeque;
+    pub use min_heap::MinHeap;
+    pub use _heap::{top_k,bottom_k};
+    pub use lazy_seq::{LazySeq,CHUNK,iterate,repeat,repeatedly,cycle,range,range_from,CheckedAdd,concat,lazy_cat};
+    pub use partition::{partition,partition_step,partition_pad,partition_all,partition_all_step,Partition};
+    pub use partition::{partition_by,PartitionBy,split_at,split_with};
+}
+pub mod xform {
+    pub use transducer::{Reduced,Reducer,Transducer,transduce,reduce,reduce_kv,comp,SeqInc,SetInc,MapInc,Step};
+    pub use transducer::{map,mapcat,filter,remove,keep,take,take_while,drop,partition_all,dedupe,distinct,interpose,cat};
+    pub use transducer::{Map,Mapcat,Filter,Remove,Keep,Take,TakeWhile,Drop,PartitionAll,Dedupe,Distinct,Interpose,Cat};
+    pub use transducer::{Partitioning,Deduping,Distincting,Interposing};
+}
+pub mod par {
+    pub use parallel::{fold,merge_all,Fold};
+}
 
 mod _str;
-pub use _str::Str;
+mod _text;
+mod grapheme;
+mod grapheme_tables;
+mod rope;
+mod small_string;
+mod keyword;
+mod value;
+mod diff;
+mod patch;
+mod walk;
+mod edn_reader;
+mod edn_printer;
+mod pretty;
+mod json_parser;
+mod json_writer;
+mod json_value;
+pub use _str::{Str,StrUnits};
+pub use _text::Text;
+pub mod string {
+    pub use _text::{Pattern,Replacement};
+    pub use grapheme::{graphemes,is_boundary,Graphemes};
+    pub use rope::Rope;
+    pub use small_string::{SmallString,INLINE};
+    pub use keyword::{Keyword,Symbol};
+}
+
+pub mod data {
+    pub use value::Value;
+    pub use diff::{diff,Diff,Parts};
+    pub use patch::{Patch,Edit,Tree};
+    pub use walk::{Walk,prewalk,postwalk,prewalk_replace,postwalk_replace,walk_keys,keywordize_keys,stringify_keys};
+}
+
+pub mod edn {
+    pub use edn_reader::{read,read_all,Reader,Error};
+    pub use edn_printer::Edn;
+}
+
+pub mod pprint {
+    pub use pretty::{pprint,Printer,Pretty,Syntax,Doc};
+}
+
+pub mod json {
+    pub use json_parser::{from_str,from_bytes,Parser,Event,FromJson,Error};
+    pub use json_writer::{to_string,to_string_pretty,Writer,ToJson};
+    pub use json_value::Json;
+}
@@ -0,0 +1,131 @@
+use _seq::Seq;
+use _heap::Heap;
+use std::collections::{BinaryHeap,binary_heap};
+use std::cmp::Reverse;
+use std::iter::{FromIterator,Map};
+use std::fmt::{Debug,Formatter,Result};
+
+/// a priority queue where the least item comes first.
+///
+/// a thin wrapper around a `BinaryHeap` of
+/// [`Reverse`](https://doc.rust-lang.org/std/cmp/struct.Reverse.html) items,
+/// so that [`Seq::dec`](../trait.Seq.html#tymethod.dec) removes the least item
+/// and the items can be accessed without unwrapping.
+///
+/// # example
+/// ```
+/// use protocoll::{Heap,Seq};
+/// use protocoll::seq::MinHeap;
+/// let h = MinHeap::new().plus(vec![3,1,4,1,5]).dec();
+/// assert_eq!(h.peek(), Some(&1));
+/// assert_eq!(h.into_sorted().collect::<Vec<_>>(), [1,3,4,5]);
+/// ```
+#[derive(Default,Clone)]
+pub struct MinHeap<T>(BinaryHeap<Reverse<T>>);
+
+impl<T> MinHeap<T> where T:Ord {
+    pub fn new() -> Self
+    {MinHeap(BinaryHeap::new())}
+
+    pub fn with_capacity(n:usize) -> Self
+    {MinHeap(BinaryHeap::with_capacity(n))}
+
+    pub fn capacity(&self) -> usize
+    {self.0.capacity()}
+
+    pub fn reserve(&mut self, n:usize)
+    {self.0.reserve(n)}
+
+    pub fn shrink_to_fit(&mut self)
+    {self.0.shrink_to_fit()}
+
+    pub fn clear(&mut self)
+    {self.0.clear()}
+
+    pub fn peek(&self) -> Option<&T>
+    {self.0.peek().map(|r| &r.0)}
+
+    /// O(log(len))
+    pub fn push(&mut self, e:T)
+    {self.0.push(Reverse(e))}
+
+    /// O(log(len))
+    pub fn pop(&mut self) -> Option<T>
+    {self.0.pop().map(|r| r.0)}
+
+    /// all items in ascending order.
+    pub fn into_sorted_vec(self) -> Vec<T>
+    {let mut v:Vec<T> = self.0.into_vec().into_iter().map(|r| r.0).collect();
+     v.sort(); v}
+}
+
+impl<T> MinHeap<T> {
+    /// iterate in arbitrary order.
+    pub fn iter(&self) -> Iter<'_,T>
+    {Iter(self.0.iter())}
+
+    pub fn len(&self) -> usize
+    {self.0.len()}
+
+    pub fn is_empty(&self) -> bool
+    {self.0.is_empty()}
+}
+
+pub struct Iter<'a,T:'a>(binary_heap::Iter<'a,Reverse<T>>);
+
+impl<'a,T> Iterator for Iter<'a,T>
+{type Item = &'a T;
+ fn next(&mut self) -> Option<&'a T> {self.0.next().map(|r| &r.0)}
+ fn size_hint(&self) -> (usize,Option<usize>) {self.0.size_hint()}}
+
+impl<T> IntoIterator for MinHeap<T>
+{type Item = T; type IntoIter = Map<binary_heap::IntoIter<Reverse<T>>,fn(Reverse<T>) -> T>;
+ fn into_iter(self) -> Self::IntoIter {self.0.into_iter().map(|r| r.0)}}
+
+impl<'a,T:'a> IntoIterator for &'a MinHeap<T>
+{type Item = &'a T; type IntoIter = Iter<'a,T>;
+ fn into_iter(self) -> Iter<'a,T> {self.iter()}}
+
+impl<T> Extend<T> for MinHeap<T> where T:Ord
+{fn extend<I>(&mut self, iter:I) where I:IntoIterator<Item = T>
+ {self.0.extend(iter.into_iter().map(Reverse))}}
+
+impl<T> FromIterator<T> for MinHeap<T> where T:Ord
+{fn from_iter<I>(iter:I) -> MinHeap<T> where I:IntoIterator<Item = T>
+ {MinHeap(iter.into_iter().map(Reverse).collect())}}
+
+impl<T> Debug for MinHeap<T> where T:Debug
+{fn fmt(&self, fmt: &mut Formatter) -> Result
+ {fmt.debug_list().entries(self.iter()).finish()}}
+
+impl<T> Seq<T> for MinHeap<T> where T:Ord {
+    /// O(n), in arbitrary order. see
+    /// [`Heap::fun_sorted`](../trait.Heap.html#tymethod.fun_sorted).
+    fn fun<'a>(&'a self) -> Box<dyn Fn(usize) -> Option<&'a T> + 'a>
+    {Box::new(move |i| self.iter().nth(i))}
+
+    fn inc(mut self, i:T) -> Self
+    {self.push(i); self}
+
+    fn dec(mut self) -> Self
+    {self.pop(); self}
+
+    fn zero(mut self) -> Self
+    {self.clear(); self}
+
+    fn shrink(mut self) -> Self
+    {self.shrink_to_fit(); self}
+}
+
+impl<T> Heap<T> for MinHeap<T> where T:Ord {
+    fn peek(&self) -> Option<&T>
+    {MinHeap::peek(self)}
+
+    fn pop_top(&mut self) -> Option<T>
+    {self.pop()}
+
+    fn fun_sorted<'a>(&'a self) -> Box<dyn Fn(usize) -> Option<&'a T> + 'a>
+    {let mut v:Vec<&T> = self.iter().collect();
+     v.sort();
+     Box::new(move |i| v.get(i).cloned())}
+}
@@ -0,0 +1,184 @@
+use _map::Map;
+use vec_sorted_map::VecSortedMap;
+use vec_sorted_set::VecSortedSet;
+use std::collections::{HashMap,HashSet};
+use std::hash::{Hash,BuildHasher};
+use std::thread;
+
+/// collections which split into pieces to be reduced on several threads.
+///
+/// seqs and sets give references to their items, and maps pairs of references
+/// to their entries.
+pub trait Fold<'a> {
+    type Item:Send + 'a;
+
+    fn len(&self) -> usize;
+
+    fn is_empty(&self) -> bool
+    {self.len() == 0}
+
+    /// splits into at most `pieces` pieces, in order.
+    fn split_into(&'a self, pieces:usize) -> Vec<Box<dyn Iterator<Item = Self::Item> + Send + 'a>>;
+}
+
+fn size(len:usize, pieces:usize) -> usize
+{len.div_ceil(pieces.max(1)).max(1)}
+
+impl<'a,T> Fold<'a> for [T] where T:Sync + 'a {
+    type Item = &'a T;
+
+    fn len(&self) -> usize
+    {<[T]>::len(self)}
+
+    fn split_into(&'a self, pieces:usize) -> Vec<Box<dyn Iterator<Item = &'a T> + Send + 'a>>
+    {self.chunks(size(self.len(), pieces)).map(|c| Box::new(c.iter()) as Box<dyn Iterator<Item = &'a T> + Send>).collect()}
+}
+
+impl<'a,T> Fold<'a> for Vec<T> where T:Sync + 'a {
+    type Item = &'a T;
+
+    fn len(&self) -> usize
+    {Vec::len(self)}
+
+    fn split_into(&'a self, pieces:usize) -> Vec<Box<dyn Iterator<Item = &'a T> + Send + 'a>>
+    {self[..].split_into(pieces)}
+}
+
+impl<'a,T> Fold<'a> for VecSortedSet<T> where T:Sync + 'a {
+    type Item = &'a T;
+
+    fn len(&self) -> usize
+    {VecSortedSet::len(self)}
+
+    fn split_into(&'a self, pieces:usize) -> Vec<Box<dyn Iterator<Item = &'a T> + Send + 'a>>
+    {self.view_content().split_into(pieces)}
+}
+
+impl<'a,K,V> Fold<'a> for VecSortedMap<K,V> where K:Sync + 'a, V:Sync + 'a {
+    type Item = (&'a K, &'a V);
+
+    fn len(&self) -> usize
+    {VecSortedMap::len(self)}
+
+    fn split_into(&'a self, pieces:usize) -> Vec<Box<dyn Iterator<Item = (&'a K, &'a V)> + Send + 'a>>
+    {let s = self.view_content();
+     s.chunks(size(s.len(), pieces))
+        .map(|c| Box::new(c.iter().map(|(k,v)| (k,v))) as Box<dyn Iterator<Item = (&'a K, &'a V)> + Send>)
+        .collect()}
+}
+
+/// a hash map has no slices to split, so its references are gathered first.
+impl<'a,K,V,S> Fold<'a> for HashMap<K,V,S> where K:Sync + Eq + Hash + 'a, V:Sync + 'a, S:BuildHasher {
+    type Item = (&'a K, &'a V);
+
+    fn len(&self) -> usize
+    {HashMap::len(self)}
+
+    fn split_into(&'a self, pieces:usize) -> Vec<Box<dyn Iterator<Item = (&'a K, &'a V)> + Send + 'a>>
+    {let n = size(self.len(), pieces);
+     let mut out:Vec<Box<dyn Iterator<Item = (&'a K, &'a V)> + Send + 'a>> = Vec::new();
+     let mut it = self.iter();
+     loop {let c:Vec<_> = it.by_ref().take(n).collect();
+           if c.is_empty() {return out}
+           out.push(Box::new(c.into_iter()))}}
+}
+
+/// a hash set has no slices to split, so its references are gathered first.
+impl<'a,T,S> Fold<'a> for HashSet<T,S> where T:Sync + Eq + Hash + 'a, S:BuildHasher {
+    type Item = &'a T;
+
+    fn len(&self) -> usize
+    {HashSet::len(self)}
+
+    fn split_into(&'a self, pieces:usize) -> Vec<Box<dyn Iterator<Item = &'a T> + Send + 'a>>
+    {let n = size(self.len(), pieces);
+     let mut out:Vec<Box<dyn Iterator<Item = &'a T> + Send + 'a>> = Vec::new();
+     let mut it = self.iter();
+     loop {let c:Vec<_> = it.by_ref().take(n).collect();
+           if c.is_empty() {return out}
+           out.push(Box::new(c.into_iter()))}}
+}
+
+fn threads() -> usize
+{thread::available_parallelism().map_or(1, |n| n.get())}
+
+/// combines adjacent pairs, level by level, until one is left, keeping the
+/// order. the pairs of a level are split into runs, at most one for each
+/// available thread, and each run is combined on a thread of its own. `None`
+/// if there is nothing.
+fn combine_tree<A,C>(mut xs:Vec<A>, combine:&C) -> Option<A> where A:Send, C:Fn(A,A) -> A + Sync {
+    let threads = threads();
+    while xs.len() > 1 {
+        let odd = if xs.len() % 2 == 1 {xs.pop()} else {None};
+        let mut pairs = Vec::with_capacity(xs.len() / 2);
+        let mut it = xs.into_iter();
+        while let (Some(a), Some(b)) = (it.next(), it.next()) {pairs.push((a,b))}
+        let n = size(pairs.len(), threads);
+        let mut runs = Vec::with_capacity(threads);
+        let mut it = pairs.into_iter();
+        loop {let run:Vec<_> = it.by_ref().take(n).collect();
+              if run.is_empty() {break}
+              runs.push(run)}
+        xs = if runs.len() == 1 {runs.pop().unwrap().into_iter().map(|(a,b)| combine(a, b)).collect()}
+        else {thread::scope(|s| {
+            let hs:Vec<_> = runs.into_iter()
+                .map(|run| s.spawn(move || run.into_iter().map(|(a,b)| combine(a, b)).collect::<Vec<_>>())).collect();
+            hs.into_iter().flat_map(|h| h.join().unwrap()).collect()})};
+        xs.extend(odd);
+    }
+    xs.pop()
+}
+
+/// reduces `coll` in parallel: it is split into pieces of at least `n` items,
+/// at most one for each available thread; each piece is reduced by `reduce`
+/// from `init()`, and the results are combined by `combine` in a tree. so
+/// `combine` must be associative, with `init()` as its identity; the order
+/// of the pieces is kept.
+///
+/// like `clojure`'s [`fold`](http://clojuredocs.org/clojure.core.reducers/fold).
+///
+/// # example
+/// ```
+/// use protocoll::par;
+/// use std::collections::HashMap;
+/// let v:Vec<u64> = (1..=100_000).collect();
+/// assert_eq!(par::fold(&v, 1000, || 0, |a, &x| a + x, |a, b| a + b), 5_000_050_000);
+/// let m:HashMap<u64,u64> = (0..1000).map(|k| (k, k % 7)).collect();
+/// let sevens = par::fold(&m, 100, || 0, |a, (_, &v)| a + (v == 0) as usize, |a, b| a + b);
+/// assert_eq!(sevens, 143);
+/// ```
+pub fn fold<'a,T,A,Z,R,C>(coll:&'a T, n:usize, init:Z, reduce:R, combine:C) -> A
+    where T:Fold<'a> + ?Sized, A:Send, Z:Fn() -> A + Sync, R:Fn(A,T::Item) -> A + Sync, C:Fn(A,A) -> A + Sync
+{let pieces = threads().min(coll.len() / n.max(1)).max(1);
+ let mut parts = coll.split_into(pieces);
+ if parts.len() <= 1
+ {return parts.pop().map_or_else(&init, |it| it.fold(init(), &reduce))}
+ let (init, reduce) = (&init, &reduce);
+ let done = thread::scope(|s| {
+     let hs:Vec<_> = parts.into_iter().map(|it| s.spawn(move || it.fold(init(), reduce))).collect();
+     hs.into_iter().map(|h| h.join().unwrap()).collect()});
+ combine_tree(done, &combine).unwrap()}
+
+/// merges all `maps` into one, resolving conflicts by `f`, in a tree of
+/// merges: adjacent pairs are merged level by level, each level on at most
+/// one thread for each available thread. `f` must be associative; the order
+/// of the maps is kept.
+///
+/// like `clojure`'s [`merge-with`](http://clojuredocs.org/clojure.core/merge-with)
+/// on many maps.
+///
+/// # example
+/// ```
+/// use protocoll::{MapMut,par};
+/// use std::collections::HashMap;
+/// use std::ops::Add;
+/// let counts:Vec<HashMap<u8,usize>> = (0..8u8).map(|t| {
+///     let mut m = HashMap::new();
+///     for i in 0..100u8 {m.update_mut(i % (t + 1), 0, |n| *n += 1)}
+///     m}).collect();
+/// let all = par::merge_all(counts, usize::add);
+/// assert_eq!(all[&0], (1..=8).map(|d| (100 + d - 1) / d).sum::<usize>());
+/// ```
+pub fn merge_all<M,I,K,V,F>(maps:I, f:F) -> M
+    where I:IntoIterator<Item = M>, M:Map<K,V> + IntoIterator<Item = (K,V)> + Default + Send, F:Fn(V,V) -> V + Sync
+{combine_tree(maps.into_iter().collect(), &|a:M, b:M| a.merge(b, &f)).unwrap_or_default()}
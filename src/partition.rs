@@ -0,0 +1,149 @@
+use _seq::Seq;
+use std::collections::VecDeque;
+use std::iter::Peekable;
+use std::marker::PhantomData;
+
+/// the chunks of [`partition`](fn.partition.html) and its variants, made as
+/// the input streams by, holding no more than one chunk of it at a time.
+pub struct Partition<I,C> where I:Iterator {
+    it:I, n:usize, step:usize, skip:usize, all:bool, pad:Option<Vec<I::Item>>,
+    buf:VecDeque<I::Item>, done:bool, chunk:PhantomData<C>,
+}
+
+fn new<I,C>(n:usize, step:usize, all:bool, pad:Option<Vec<I::Item>>, coll:I) -> Partition<I::IntoIter,C> where I:IntoIterator
+{assert!(n > 0 && step > 0, "partitions need a positive size and step");
+ Partition{it:coll.into_iter(), n, step, skip:0, all, pad, buf:VecDeque::with_capacity(n), done:false, chunk:PhantomData}}
+
+/// chunks of `n` items; an incomplete one at the end is dropped.
+///
+/// like `clojure`'s [`partition`](http://clojuredocs.org/clojure.core/partition).
+///
+/// # example
+/// ```
+/// use protocoll::seq;
+/// use std::collections::VecDeque;
+/// let p:Vec<Vec<_>> = seq::partition(3, 0..8).collect();
+/// assert_eq!(p, [[0,1,2], [3,4,5]]);
+/// let p:Vec<VecDeque<_>> = seq::partition_step(3, 2, 0..8).collect();
+/// assert_eq!(p, [[0,1,2], [2,3,4], [4,5,6]]);
+/// let p:Vec<Vec<_>> = seq::partition_pad(3, 2, vec![-1], 0..8).collect();
+/// assert_eq!(p, [vec![0,1,2], vec![2,3,4], vec![4,5,6], vec![6,7,-1]]);
+/// let p:Vec<Vec<_>> = seq::partition_all_step(3, 2, 0..8).collect();
+/// assert_eq!(p, [vec![0,1,2], vec![2,3,4], vec![4,5,6], vec![6,7]]);
+/// let p:Vec<Vec<_>> = seq::partition_all_step(2, 3, 0..8).collect();
+/// assert_eq!(p, [vec![0,1], vec![3,4], vec![6,7]]);
+/// ```
+pub fn partition<C,I>(n:usize, coll:I) -> Partition<I::IntoIter,C> where I:IntoIterator
+{new(n, n, false, None, coll)}
+
+/// chunks of `n` items starting every `step` items, as sliding windows when
+/// `step` is less than `n`; incomplete ones at the end are dropped.
+pub fn partition_step<C,I>(n:usize, step:usize, coll:I) -> Partition<I::IntoIter,C> where I:IntoIterator
+{new(n, step, false, None, coll)}
+
+/// like [`partition_step`](fn.partition_step.html), but the first incomplete
+/// chunk at the end is filled up with items from `pad`, and kept even if
+/// `pad` runs out.
+pub fn partition_pad<C,I,P>(n:usize, step:usize, pad:P, coll:I) -> Partition<I::IntoIter,C>
+    where I:IntoIterator, P:IntoIterator<Item = I::Item>
+{new(n, step, false, Some(pad.into_iter().take(n).collect()), coll)}
+
+/// chunks of `n` items; an incomplete one at the end is kept.
+///
+/// like `clojure`'s [`partition-all`](http://clojuredocs.org/clojure.core/partition-all).
+pub fn partition_all<C,I>(n:usize, coll:I) -> Partition<I::IntoIter,C> where I:IntoIterator
+{new(n, n, true, None, coll)}
+
+/// chunks of `n` items starting every `step` items; all the incomplete ones
+/// at the end are kept.
+pub fn partition_all_step<C,I>(n:usize, step:usize, coll:I) -> Partition<I::IntoIter,C> where I:IntoIterator
+{new(n, step, true, None, coll)}
+
+impl<I,C> Partition<I,C> where I:Iterator, I::Item:Clone, C:Seq<I::Item> + Default {
+    /// the chunk of the buffer, dropping the first `step` items from it.
+    fn emit(&mut self) -> C
+    {if self.step >= self.buf.len()
+     {self.skip = self.step - self.buf.len();
+      C::default().plus(self.buf.drain(..))}
+     else
+     {let c = C::default().plus(self.buf.iter().cloned());
+      self.buf.drain(..self.step);
+      c}}
+}
+
+impl<I,C> Iterator for Partition<I,C> where I:Iterator, I::Item:Clone, C:Seq<I::Item> + Default {
+    type Item = C;
+    fn next(&mut self) -> Option<C> {
+        while self.skip > 0 && !self.done
+        {self.skip -= 1; if self.it.next().is_none() {self.done = true}}
+        while self.buf.len() < self.n && !self.done
+        {match self.it.next() {Some(t) => self.buf.push_back(t), None => self.done = true}}
+        if self.buf.len() == self.n {return Some(self.emit())}
+        if self.buf.is_empty() {return None}
+        if self.all {return Some(self.emit())}
+        let pad = self.pad.take()?;
+        let room = self.n - self.buf.len();
+        self.buf.extend(pad.into_iter().take(room));
+        Some(C::default().plus(self.buf.drain(..)))
+    }
+}
+
+/// the runs of [`partition_by`](fn.partition_by.html).
+pub struct PartitionBy<I,F,K,C> where I:Iterator {it:I, f:F, pending:Option<(I::Item,K)>, chunk:PhantomData<C>}
+
+/// chunks of consecutive items for which `f` gives equal values. `f` is
+/// called once for each item.
+///
+/// like `clojure`'s [`partition-by`](http://clojuredocs.org/clojure.core/partition-by).
+///
+/// # example
+/// ```
+/// use protocoll::seq;
+/// let p:Vec<Vec<_>> = seq::partition_by(|n:&i32| n % 2, vec![1,3,2,4,6,5]).collect();
+/// assert_eq!(p, [vec![1,3], vec![2,4,6], vec![5]]);
+/// ```
+pub fn partition_by<C,I,F,K>(f:F, coll:I) -> PartitionBy<I::IntoIter,F,K,C>
+    where I:IntoIterator, F:FnMut(&I::Item) -> K, K:PartialEq
+{PartitionBy{it:coll.into_iter(), f, pending:None, chunk:PhantomData}}
+
+impl<I,F,K,C> Iterator for PartitionBy<I,F,K,C>
+    where I:Iterator, F:FnMut(&I::Item) -> K, K:PartialEq, C:Seq<I::Item> + Default {
+    type Item = C;
+    fn next(&mut self) -> Option<C> {
+        let (t,k) = match self.pending.take()
+        {Some(p) => p, None => {let t = self.it.next()?; let k = (self.f)(&t); (t,k)}};
+        let mut c = C::default().inc(t);
+        for u in self.it.by_ref() {
+            let ku = (self.f)(&u);
+            if ku == k {c = c.inc(u)} else {self.pending = Some((u,ku)); break}
+        }
+        Some(c)
+    }
+}
+
+/// the first `n` items as a chunk, and the rest of `coll`.
+///
+/// like `clojure`'s [`split-at`](http://clojuredocs.org/clojure.core/split-at).
+///
+/// # example
+/// ```
+/// use protocoll::seq;
+/// let (a, rest):(Vec<_>,_) = seq::split_at(2, 0..5);
+/// assert_eq!((a, rest.collect::<Vec<_>>()), (vec![0,1], vec![2,3,4]));
+/// let (a, mut rest):(Vec<_>,_) = seq::split_with(|&n| n < 3, 0..);
+/// assert_eq!((a, rest.next()), (vec![0,1,2], Some(3)));
+/// ```
+pub fn split_at<C,I>(n:usize, coll:I) -> (C, I::IntoIter) where I:IntoIterator, C:Seq<I::Item> + Default
+{let mut it = coll.into_iter();
+ (C::default().plus(it.by_ref().take(n)), it)}
+
+/// the items while `p` holds as a chunk, and the rest of `coll`, which starts
+/// with the item that stopped it.
+///
+/// like `clojure`'s [`split-with`](http://clojuredocs.org/clojure.core/split-with).
+pub fn split_with<C,I,P>(mut p:P, coll:I) -> (C, Peekable<I::IntoIter>)
+    where I:IntoIterator, C:Seq<I::Item> + Default, P:FnMut(&I::Item) -> bool
+{let mut it = coll.into_iter().peekable();
+ let mut c = C::default();
+ while let Some(t) = it.next_if(&mut p) {c = c.inc(t)}
+ (c, it)}
@@ -0,0 +1,179 @@
+use value::Value;
+use json_value::Json;
+use vec_sorted_map::VecSortedMap;
+use std::fmt::Debug;
+
+/// dynamic values which are either maps of keys to values of their own type,
+/// which a [`Patch`](enum.Patch.html) goes into, or leaves.
+pub trait Tree where Self:Clone + PartialEq + Default {
+    type Key:Ord + Clone + Debug;
+
+    /// the entries, if this is a map.
+    fn entries(&self) -> Option<&VecSortedMap<Self::Key,Self>>;
+
+    /// the entries, if this is a map.
+    fn into_entries(self) -> Option<VecSortedMap<Self::Key,Self>>;
+
+    /// the map of `entries`.
+    fn from_entries(entries:VecSortedMap<Self::Key,Self>) -> Self;
+}
+
+/// maps are the ones of [`Value::Map`](enum.Value.html#variant.Map).
+impl Tree for Value {
+    type Key = Value;
+
+    fn entries(&self) -> Option<&VecSortedMap<Value,Value>>
+    {match *self {Value::Map(ref m) => Some(m), _ => None}}
+
+    fn into_entries(self) -> Option<VecSortedMap<Value,Value>>
+    {match self {Value::Map(m) => Some(m), _ => None}}
+
+    fn from_entries(entries:VecSortedMap<Value,Value>) -> Value
+    {Value::Map(entries)}
+}
+
+/// maps are the ones of [`Json::Object`](../json/enum.Json.html#variant.Object).
+impl Tree for Json {
+    type Key = String;
+
+    fn entries(&self) -> Option<&VecSortedMap<String,Json>>
+    {match *self {Json::Object(ref m) => Some(m), _ => None}}
+
+    fn into_entries(self) -> Option<VecSortedMap<String,Json>>
+    {match self {Json::Object(m) => Some(m), _ => None}}
+
+    fn from_entries(entries:VecSortedMap<String,Json>) -> Json
+    {Json::Object(entries)}
+}
+
+/// the changes from one [`Tree`](trait.Tree.html) to another: the entries
+/// added, removed and changed, recursively into the maps in both, and any
+/// other value replaced as a whole. a patch keeps the old values as well as
+/// the new ones, so that it can be inverted.
+///
+/// # example
+/// ```
+/// use protocoll::data::{Patch,Edit,Value};
+/// use protocoll::edn;
+/// let a = edn::read("{:db {:host \"localhost\", :port 5432}, :debug true}").unwrap();
+/// let b = edn::read("{:db {:host \"db.internal\", :port 5432}, :tags [:x]}").unwrap();
+/// let p = Patch::between(&a, &b);
+/// assert_eq!(p.apply(a.clone()), b);
+/// assert_eq!(p.clone().invert().apply(b.clone()), a);
+/// match p {Patch::Entries(ref es) => assert_eq!(es.len(), 3), _ => unreachable!()}
+/// let c = edn::read("{:db {:host \"db.internal\", :port 6543}}").unwrap();
+/// let q = Patch::between(&b, &c);
+/// let pq = p.compose(q);
+/// assert_eq!(pq, Patch::between(&a, &c));
+/// assert_eq!(pq.apply(a), c);
+/// ```
+#[derive(Clone,PartialEq,Debug,Default)]
+pub enum Patch<V> where V:Tree {
+    /// nothing changes.
+    #[default]
+    Keep,
+    /// the old value is replaced by the new one.
+    Replace(V,V),
+    /// the entries of a map change; each key in it is added, removed or
+    /// changed.
+    Entries(VecSortedMap<V::Key,Edit<V>>),
+}
+
+/// the change of an entry of a map in a [`Patch`](enum.Patch.html).
+#[derive(Clone,PartialEq,Debug)]
+pub enum Edit<V> where V:Tree {
+    /// the entry with the new value is added.
+    Add(V),
+    /// the entry with the old value is removed.
+    Remove(V),
+    /// the value of the entry changes.
+    Change(Patch<V>),
+}
+
+impl<V> Patch<V> where V:Tree {
+    /// the changes from `a` to `b`.
+    pub fn between(a:&V, b:&V) -> Self {
+        if a == b {return Patch::Keep}
+        match (a.entries(), b.entries()) {
+            (Some(ma), Some(mb)) => {
+                let mut es = VecSortedMap::new();
+                for (k, va) in ma.iter()
+                {match mb.get(k)
+                 {None => {es.insert(k.clone(), Edit::Remove(va.clone()));}
+                  Some(vb) => if va != vb {es.insert(k.clone(), Edit::Change(Patch::between(va, vb)));}}}
+                for (k, vb) in mb.iter()
+                {if !ma.contains_key(k) {es.insert(k.clone(), Edit::Add(vb.clone()));}}
+                Patch::Entries(es)
+            }
+            _ => Patch::Replace(a.clone(), b.clone()),
+        }
+    }
+
+    /// whether nothing changes.
+    pub fn is_empty(&self) -> bool
+    {matches!(*self, Patch::Keep)}
+
+    /// applies the changes to `v`. the old values are not checked: a
+    /// replacement gives the new value whatever `v` is, and entries change
+    /// `v` as a map, or an empty one if it is not. a change to a missing
+    /// entry applies to the default value.
+    pub fn apply(&self, v:V) -> V {
+        match *self {
+            Patch::Keep => v,
+            Patch::Replace(_, ref new) => new.clone(),
+            Patch::Entries(ref es) => {
+                let mut m = v.into_entries().unwrap_or_else(VecSortedMap::new);
+                for (k, e) in es.iter()
+                {match *e
+                 {Edit::Add(ref new) => {m.insert(k.clone(), new.clone());}
+                  Edit::Remove(_) => {m.remove(k);}
+                  Edit::Change(ref p) => {let old = m.remove(k).unwrap_or_default(); m.insert(k.clone(), p.apply(old));}}}
+                V::from_entries(m)
+            }
+        }
+    }
+
+    /// the changes back: from the new values to the old ones.
+    pub fn invert(self) -> Self {
+        match self {
+            Patch::Keep => Patch::Keep,
+            Patch::Replace(old, new) => Patch::Replace(new, old),
+            Patch::Entries(es) =>
+                Patch::Entries(es.into_iter().map(|(k,e)| (k, match e
+                                                           {Edit::Add(v) => Edit::Remove(v),
+                                                            Edit::Remove(v) => Edit::Add(v),
+                                                            Edit::Change(p) => Edit::Change(p.invert())})).collect()),
+        }
+    }
+
+    /// the changes of this patch followed by those of `next`, as one patch,
+    /// which is meant to apply to what this one gives. if `next` does not,
+    /// e.g. when it adds an entry this one has kept, its changes win.
+    pub fn compose(self, next:Self) -> Self {
+        match (self, next) {
+            (Patch::Keep, q) => q,
+            (p, Patch::Keep) => p,
+            (Patch::Replace(a, b), q) => Patch::between(&a, &q.apply(b)),
+            (p, Patch::Replace(b, c)) => Patch::between(&p.invert().apply(b), &c),
+            (Patch::Entries(mut es), Patch::Entries(next)) => {
+                for (k,e) in next
+                {let e = match es.remove(&k) {Some(d) => compose_edit(d, e), None => Some(e)};
+                 if let Some(e) = e {es.insert(k, e);}}
+                if es.is_empty() {Patch::Keep} else {Patch::Entries(es)}
+            }
+        }
+    }
+}
+
+/// the edit `e` followed by `next`, or `None` if they cancel out.
+fn compose_edit<V>(e:Edit<V>, next:Edit<V>) -> Option<Edit<V>> where V:Tree {
+    let change = |p:Patch<V>| if p.is_empty() {None} else {Some(Edit::Change(p))};
+    match (e, next) {
+        (Edit::Add(_), Edit::Remove(_)) => None,
+        (Edit::Add(v), Edit::Change(q)) => Some(Edit::Add(q.apply(v))),
+        (Edit::Remove(old), Edit::Add(new)) => change(Patch::between(&old, &new)),
+        (Edit::Change(p), Edit::Remove(new)) => Some(Edit::Remove(p.invert().apply(new))),
+        (Edit::Change(p), Edit::Change(q)) => change(p.compose(q)),
+        (_, next) => Some(next),
+    }
+}
@@ -0,0 +1,208 @@
+use _seq::Seq;
+use _deque::Deque;
+use std::rc::Rc;
+use std::iter::FromIterator;
+use std::fmt::{Debug,Formatter,Result};
+
+struct Node<T> {item:T, next:List<T>}
+
+type List<T> = Option<Rc<Node<T>>>;
+
+fn cons<T>(item:T, next:List<T>) -> List<T>
+{Some(Rc::new(Node{item, next}))}
+
+/// a persistent deque. cloning is O(1) and the clones share their structure.
+///
+/// made of two shared singly linked lists, one for each end. when one end runs
+/// dry, half of the other end is copied over, which is why most functions
+/// require `T:Clone`. operations at both ends are amortized O(1) as long as
+/// each version is only used once, single-threaded; an old version used again
+/// may repeat the same O(n) copy each time, so the worst case is O(n).
+///
+/// as a [`Seq`](../trait.Seq.html) it behaves like a queue, just like
+/// `clojure`'s `PersistentQueue`: `inc` grows to the right and `dec` removes
+/// from the left.
+///
+/// # example
+/// ```
+/// use protocoll::{Deque,Seq};
+/// use protocoll::seq::PersistentDeque;
+/// let q:PersistentDeque<_> = (0..4).collect();
+/// let p = q.clone().dec_front().inc_front(9);
+/// assert_eq!(q.iter().cloned().collect::<Vec<_>>(), [0,1,2,3]);
+/// assert_eq!(p.iter().cloned().collect::<Vec<_>>(), [9,1,2,3]);
+/// assert_eq!(p.dec().peek_front(), Some(&1));
+/// let long = (0..1_000_000).fold(PersistentDeque::new(), |q,i| q.inc_front(i));
+/// let mut it = long.into_iter();
+/// assert_eq!(it.next(), Some(999_999));
+/// drop(it);
+/// ```
+pub struct PersistentDeque<T> {front:List<T>, flen:usize, back:List<T>, blen:usize}
+
+impl<T> PersistentDeque<T> {
+    pub fn new() -> Self
+    {PersistentDeque{front:None, flen:0, back:None, blen:0}}
+
+    pub fn len(&self) -> usize
+    {self.flen + self.blen}
+
+    pub fn is_empty(&self) -> bool
+    {self.len() == 0}
+
+    /// iterate from front to back.
+    pub fn iter(&self) -> Iter<'_,T>
+    {let mut back = Vec::with_capacity(self.blen);
+     let mut node = self.back.as_ref();
+     while let Some(n) = node {back.push(&n.item); node = n.next.as_ref()}
+     Iter{front:self.front.as_deref(), back}}
+}
+
+impl<T> PersistentDeque<T> where T:Clone {
+    /// restores the invariant that both ends are non-empty when there are at
+    /// least two items, by moving half of one end over to the other.
+    fn balance(mut self) -> Self {
+        if self.flen == 0 && self.blen > 1 {
+            let (keep,moved) = split(&self.back, self.blen);
+            self.front = moved; self.flen = self.blen - self.blen / 2;
+            self.back = keep; self.blen /= 2;
+        } else if self.blen == 0 && self.flen > 1 {
+            let (keep,moved) = split(&self.front, self.flen);
+            self.back = moved; self.blen = self.flen - self.flen / 2;
+            self.front = keep; self.flen /= 2;
+        } self
+    }
+}
+
+/// splits a list of length `n` into its first `n/2` items and the rest in
+/// reverse order.
+fn split<T>(list:&List<T>, n:usize) -> (List<T>,List<T>) where T:Clone {
+    let mut items = Vec::with_capacity(n);
+    let mut node = list.as_ref();
+    while let Some(n) = node {items.push(&n.item); node = n.next.as_ref()}
+    let k = n / 2;
+    let keep = items[..k].iter().rev().fold(None, |l,&i| cons(i.clone(), l));
+    let moved = items[k..].iter().fold(None, |l,&i| cons(i.clone(), l));
+    (keep,moved)
+}
+
+/// drops the unshared part of `list` iteratively, so that long lists do not
+/// overflow the stack.
+fn drop_list<T>(list:&mut List<T>) {
+    let mut node = list.take();
+    while let Some(rc) = node {
+        node = match Rc::try_unwrap(rc) {Ok(mut n) => n.next.take(), Err(_) => None}
+    }
+}
+
+impl<T> Drop for PersistentDeque<T>
+{fn drop(&mut self) {drop_list(&mut self.front); drop_list(&mut self.back)}}
+
+impl<T> Clone for PersistentDeque<T>
+{fn clone(&self) -> Self
+ {PersistentDeque{front:self.front.clone(), flen:self.flen, back:self.back.clone(), blen:self.blen}}}
+
+impl<T> Default for PersistentDeque<T>
+{fn default() -> Self {PersistentDeque::new()}}
+
+impl<T> PartialEq for PersistentDeque<T> where T:PartialEq
+{fn eq(&self, other:&Self) -> bool
+ {self.len() == other.len() && self.iter().eq(other.iter())}}
+
+impl<T> Eq for PersistentDeque<T> where T:Eq {}
+
+impl<T> Debug for PersistentDeque<T> where T:Debug
+{fn fmt(&self, fmt: &mut Formatter) -> Result
+ {fmt.debug_list().entries(self.iter()).finish()}}
+
+pub struct Iter<'a,T:'a> {front:Option<&'a Node<T>>, back:Vec<&'a T>}
+
+impl<'a,T> Iterator for Iter<'a,T>
+{type Item = &'a T;
+ fn next(&mut self) -> Option<&'a T>
+ {match self.front
+  {Some(n) => {self.front = n.next.as_deref(); Some(&n.item)}
+   None => self.back.pop()}}}
+
+impl<'a,T:'a> IntoIterator for &'a PersistentDeque<T>
+{type Item = &'a T; type IntoIter = Iter<'a,T>;
+ fn into_iter(self) -> Iter<'a,T> {self.iter()}}
+
+/// moves out the items which are not shared, and clones the rest.
+pub struct IntoIter<T> {front:List<T>, back:Vec<T>}
+
+impl<T> Drop for IntoIter<T>
+{fn drop(&mut self) {drop_list(&mut self.front)}}
+
+impl<T> Iterator for IntoIter<T> where T:Clone
+{type Item = T;
+ fn next(&mut self) -> Option<T>
+ {match self.front.take()
+  {Some(rc) => {let (i,next) = match Rc::try_unwrap(rc)
+                 {Ok(n) => (n.item, n.next),
+                  Err(rc) => (rc.item.clone(), rc.next.clone())};
+                self.front = next; Some(i)}
+   None => self.back.pop()}}}
+
+impl<T> IntoIterator for PersistentDeque<T> where T:Clone
+{type Item = T; type IntoIter = IntoIter<T>;
+ fn into_iter(mut self) -> IntoIter<T>
+ {let mut back = Vec::with_capacity(self.blen);
+  let mut node = self.back.take();
+  while let Some(rc) = node
+  {node = match Rc::try_unwrap(rc)
+   {Ok(n) => {back.push(n.item); n.next}
+    Err(rc) => {back.push(rc.item.clone()); rc.next.clone()}}}
+  IntoIter{front:self.front.take(), back}}}
+
+impl<T> Extend<T> for PersistentDeque<T> where T:Clone
+{fn extend<I>(&mut self, iter:I) where I:IntoIterator<Item = T>
+ {let q = Seq::plus(::std::mem::take(self), iter);
+  *self = q}}
+
+impl<T> FromIterator<T> for PersistentDeque<T> where T:Clone
+{fn from_iter<I>(iter:I) -> PersistentDeque<T> where I:IntoIterator<Item = T>
+ {Seq::plus(PersistentDeque::new(), iter)}}
+
+impl<T> Deque<T> for PersistentDeque<T> where T:Clone {
+    fn inc_front(mut self, i:T) -> Self
+    {self.front = cons(i, self.front.take()); self.flen += 1; self.balance()}
+
+    fn inc_back(mut self, i:T) -> Self
+    {self.back = cons(i, self.back.take()); self.blen += 1; self.balance()}
+
+    fn dec_front(mut self) -> Self
+    {match self.front.take()
+     {Some(n) => {self.front = n.next.clone(); self.flen -= 1}
+      None => {self.back = None; self.blen = 0}}
+     self.balance()}
+
+    fn dec_back(mut self) -> Self
+    {match self.back.take()
+     {Some(n) => {self.back = n.next.clone(); self.blen -= 1}
+      None => {self.front = None; self.flen = 0}}
+     self.balance()}
+
+    fn peek_front(&self) -> Option<&T>
+    {self.front.as_ref().or(self.back.as_ref()).map(|n| &n.item)}
+
+    fn peek_back(&self) -> Option<&T>
+    {self.back.as_ref().or(self.front.as_ref()).map(|n| &n.item)}
+}
+
+impl<T> Seq<T> for PersistentDeque<T> where T:Clone {
+    /// O(n).
+    fn fun<'a>(&'a self) -> Box<dyn Fn(usize) -> Option<&'a T> + 'a>
+    {Box::new(move |i| self.iter().nth(i))}
+
+    fn inc(self, i:T) -> Self
+    {Deque::inc_back(self,i)}
+
+    fn dec(self) -> Self
+    {Deque::dec_front(self)}
+
+    fn zero(self) -> Self
+    {PersistentDeque::new()}
+
+    fn shrink(self) -> Self
+    {self}
+}
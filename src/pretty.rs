@@ -0,0 +1,335 @@
+use value::Value;
+use keyword::{Keyword,Symbol};
+use edn_printer::Edn;
+use vec_sorted_map::VecSortedMap;
+use vec_sorted_set::VecSortedSet;
+use persistent_deque::PersistentDeque;
+use std::collections::{HashMap,BTreeMap,HashSet,BTreeSet,VecDeque,LinkedList};
+use std::hash::Hash;
+use std::borrow::Cow;
+use std::fmt::Debug;
+
+/// a document to lay out, as in wadler's "a prettier printer".
+///
+/// a [`Group`](#variant.Group) is laid out flat, with its lines as their flat
+/// text, if it fits into the rest of the line; otherwise its lines are broken,
+/// which its inner groups then decide for themselves again.
+#[derive(Clone,Debug)]
+pub enum Doc {
+    Text(Cow<'static,str>),
+    /// a line break, or the text when laid out flat.
+    Line(&'static str),
+    Concat(Vec<Doc>),
+    /// indents the lines by more.
+    Nest(usize, Box<Doc>),
+    /// indents the lines to the column where it starts.
+    Align(Box<Doc>),
+    Group(Box<Doc>),
+}
+
+impl Doc {
+    pub fn text<S>(s:S) -> Doc where S:Into<Cow<'static,str>>
+    {Doc::Text(s.into())}
+
+    /// a line break, or a space when flat.
+    pub fn line() -> Doc
+    {Doc::Line(" ")}
+
+    /// a line break, or nothing when flat.
+    pub fn softline() -> Doc
+    {Doc::Line("")}
+
+    pub fn concat<I>(docs:I) -> Doc where I:IntoIterator<Item = Doc>
+    {Doc::Concat(docs.into_iter().collect())}
+
+    pub fn nest(self, i:usize) -> Doc
+    {Doc::Nest(i, Box::new(self))}
+
+    pub fn align(self) -> Doc
+    {Doc::Align(Box::new(self))}
+
+    pub fn group(self) -> Doc
+    {Doc::Group(Box::new(self))}
+
+    /// lays out with a right margin at column `width`. a text wider than that
+    /// still goes over.
+    pub fn render(&self, width:usize) -> String {
+        let mut out = String::new();
+        let mut col = 0;
+        let mut stack = vec![(0, false, self)];
+        while let Some((i, flat, d)) = stack.pop() {
+            match *d {
+                Doc::Text(ref s) => {out.push_str(s); col += s.chars().count()}
+                Doc::Line(s) if flat => {out.push_str(s); col += s.chars().count()}
+                Doc::Line(_) => {
+                    out.push('\n');
+                    out.extend((0..i).map(|_| ' '));
+                    col = i
+                }
+                Doc::Concat(ref ds) => stack.extend(ds.iter().rev().map(|d| (i, flat, d))),
+                Doc::Nest(j, ref d) => stack.push((i + j, flat, d)),
+                Doc::Align(ref d) => stack.push((col, flat, d)),
+                Doc::Group(ref d) =>
+                {let flat = flat || fits(width as isize - col as isize, d, &stack);
+                 stack.push((i, flat, d))}
+            }
+        }
+        out
+    }
+
+    /// lays out all on one line.
+    pub fn flat(&self) -> String
+    {let mut s = String::new(); self.write_flat(&mut s); s}
+
+    fn write_flat(&self, out:&mut String)
+    {match *self
+     {Doc::Text(ref s) => out.push_str(s),
+      Doc::Line(s) => out.push_str(s),
+      Doc::Concat(ref ds) => for d in ds {d.write_flat(out)},
+      Doc::Nest(_, ref d) | Doc::Align(ref d) | Doc::Group(ref d) => d.write_flat(out)}}
+}
+
+/// whether `d` laid out flat, and what follows it up to the next line break,
+/// fit into `rem` columns.
+fn fits(mut rem:isize, d:&Doc, rest:&[(usize,bool,&Doc)]) -> bool {
+    let mut stack = vec![(true, d)];
+    let mut rest = rest.iter().rev();
+    while rem >= 0 {
+        let (flat, d) = match stack.pop()
+        {Some(x) => x,
+         None => match rest.next() {Some(&(_, flat, d)) => (flat, d), None => return true}};
+        match *d {
+            Doc::Text(ref s) => rem -= s.chars().count() as isize,
+            Doc::Line(s) if flat => rem -= s.chars().count() as isize,
+            Doc::Line(_) => return true,
+            Doc::Concat(ref ds) => stack.extend(ds.iter().rev().map(|d| (flat, d))),
+            Doc::Nest(_, ref d) | Doc::Align(ref d) => stack.push((flat, d)),
+            Doc::Group(ref d) => stack.push((flat, d)),
+        }
+    }
+    false
+}
+
+/// the syntax to print in.
+#[derive(Clone,Copy,PartialEq,Eq,Debug)]
+pub enum Syntax {
+    /// `{:a 1, :b [2 3]}`, `#{1 2}`, `"s"`, `nil`.
+    Edn,
+    /// like `{:?}`: `{"a": 1, "b": [2, 3]}`, `{1, 2}`, `None`.
+    Debug,
+}
+
+/// how to pretty print.
+///
+/// like `clojure`'s [`pprint`](http://clojuredocs.org/clojure.pprint/pprint),
+/// a collection goes on one line if it fits, and otherwise puts each item on a
+/// line of its own, aligned after the opening bracket. the entries of hash
+/// maps and the items of hash sets are sorted by their printed text, so the
+/// output does not depend on the hashing.
+///
+/// collections nested deeper than [`max_depth`](#method.max_depth) print as
+/// `#`, and items beyond [`max_length`](#method.max_length) as `...`, like
+/// `*print-level*` and `*print-length*`.
+///
+/// # example
+/// ```
+/// use protocoll::pprint::{Printer,Syntax,pprint};
+/// use std::collections::HashMap;
+/// let mut m = HashMap::new();
+/// m.insert("b", vec![vec![1,2,3], vec![4,5,6]]);
+/// m.insert("a", vec![]);
+/// assert_eq!(pprint(&m), r#"{"a" [], "b" [[1 2 3] [4 5 6]]}"#);
+/// assert_eq!(Printer::new().width(20).print(&m),
+/// r#"{"a" [],
+///  "b" [[1 2 3]
+///       [4 5 6]]}"#);
+/// assert_eq!(Printer::new().syntax(Syntax::Debug).max_depth(2).max_length(1).print(&m),
+///            r#"{"a": [], ...}"#);
+/// assert_eq!(Printer::new().max_depth(2).print(&m), r#"{"a" [], "b" [# #]}"#);
+/// ```
+#[derive(Clone,Debug)]
+pub struct Printer {width:usize, max_depth:Option<usize>, max_length:Option<usize>, syntax:Syntax}
+
+impl Default for Printer
+{fn default() -> Printer {Printer::new()}}
+
+impl Printer {
+    /// a margin of 80 columns, no truncation, and edn syntax.
+    pub fn new() -> Printer
+    {Printer{width:80, max_depth:None, max_length:None, syntax:Syntax::Edn}}
+
+    /// the right margin.
+    pub fn width(mut self, width:usize) -> Printer
+    {self.width = width; self}
+
+    /// how many levels of collections to print.
+    pub fn max_depth(mut self, depth:usize) -> Printer
+    {self.max_depth = Some(depth); self}
+
+    /// how many items of each collection to print.
+    pub fn max_length(mut self, length:usize) -> Printer
+    {self.max_length = Some(length); self}
+
+    pub fn syntax(mut self, syntax:Syntax) -> Printer
+    {self.syntax = syntax; self}
+
+    pub fn get_syntax(&self) -> Syntax
+    {self.syntax}
+
+    pub fn print<T>(&self, t:&T) -> String where T:Pretty + ?Sized
+    {t.to_doc(self, 0).render(self.width)}
+
+    /// the doc of a collection at `depth` with `open` and `close` brackets.
+    pub fn seq_doc<'a,T,I>(&self, depth:usize, open:&'static str, close:&'static str, items:I) -> Doc
+        where T:Pretty + ?Sized + 'a, I:IntoIterator<Item = &'a T>
+    {self.coll_doc(depth, open, close, self.item_sep(), items.into_iter().map(|t| t.to_doc(self, depth + 1)))}
+
+    /// the doc of a map at `depth`. `sort` sorts the entries by the printed
+    /// keys.
+    pub fn map_doc<'a,K,V,I>(&self, depth:usize, entries:I, sort:bool) -> Doc
+        where K:Pretty + ?Sized + 'a, V:Pretty + ?Sized + 'a, I:IntoIterator<Item = (&'a K, &'a V)>
+    {let sep = match self.syntax {Syntax::Edn => " ", Syntax::Debug => ": "};
+     let entry = |(k,v):(&K,&V)| Doc::concat(vec![k.to_doc(self, depth + 1), Doc::text(sep), v.to_doc(self, depth + 1)]);
+     if sort && !self.too_deep(depth)
+     {let mut es:Vec<_> = entries.into_iter().map(|(k,v)| (k.to_doc(self, depth + 1).flat(), (k,v))).collect();
+      es.sort_by(|a,b| a.0.cmp(&b.0));
+      self.coll_doc(depth, "{", "}", ",", es.into_iter().map(|(_,e)| entry(e)))}
+     else {self.coll_doc(depth, "{", "}", ",", entries.into_iter().map(entry))}}
+
+    /// like [`seq_doc`](#method.seq_doc) but sorts the items by their printed
+    /// text.
+    pub fn sorted_seq_doc<'a,T,I>(&self, depth:usize, open:&'static str, close:&'static str, items:I) -> Doc
+        where T:Pretty + ?Sized + 'a, I:IntoIterator<Item = &'a T>
+    {if self.too_deep(depth) {return Doc::text("#")}
+     let mut ds:Vec<_> = items.into_iter().map(|t| {let d = t.to_doc(self, depth + 1); (d.flat(), d)}).collect();
+     ds.sort_by(|a,b| a.0.cmp(&b.0));
+     self.coll_doc(depth, open, close, self.item_sep(), ds.into_iter().map(|(_,d)| d))}
+
+    fn item_sep(&self) -> &'static str
+    {match self.syntax {Syntax::Edn => "", Syntax::Debug => ","}}
+
+    fn too_deep(&self, depth:usize) -> bool
+    {self.max_depth.is_some_and(|m| depth >= m)}
+
+    fn coll_doc<I>(&self, depth:usize, open:&'static str, close:&'static str, sep:&'static str, items:I) -> Doc
+        where I:Iterator<Item = Doc> {
+        if self.too_deep(depth) {return Doc::text("#")}
+        let mut body = Vec::new();
+        for (i,d) in items.enumerate() {
+            if i > 0 {body.push(Doc::text(sep)); body.push(Doc::line())}
+            if self.max_length.is_some_and(|m| i >= m) {body.push(Doc::text("...")); break}
+            body.push(d)
+        }
+        Doc::concat(vec![Doc::text(open), Doc::Concat(body).align(), Doc::text(close)]).group()
+    }
+
+    fn atom<T>(&self, t:&T) -> Doc where T:Edn + Debug + ?Sized
+    {Doc::text(match self.syntax {Syntax::Edn => t.to_edn(), Syntax::Debug => format!("{:?}", t)})}
+}
+
+/// pretty prints `t` with the default [`Printer`](struct.Printer.html).
+pub fn pprint<T>(t:&T) -> String where T:Pretty + ?Sized
+{Printer::new().print(t)}
+
+/// things which can be pretty printed.
+pub trait Pretty {
+    /// the doc of `self` nested in `depth` collections.
+    fn to_doc(&self, p:&Printer, depth:usize) -> Doc;
+}
+
+impl Pretty for bool {fn to_doc(&self, p:&Printer, _:usize) -> Doc {p.atom(self)}}
+impl Pretty for i8 {fn to_doc(&self, p:&Printer, _:usize) -> Doc {p.atom(self)}}
+impl Pretty for i16 {fn to_doc(&self, p:&Printer, _:usize) -> Doc {p.atom(self)}}
+impl Pretty for i32 {fn to_doc(&self, p:&Printer, _:usize) -> Doc {p.atom(self)}}
+impl Pretty for i64 {fn to_doc(&self, p:&Printer, _:usize) -> Doc {p.atom(self)}}
+impl Pretty for isize {fn to_doc(&self, p:&Printer, _:usize) -> Doc {p.atom(self)}}
+impl Pretty for u8 {fn to_doc(&self, p:&Printer, _:usize) -> Doc {p.atom(self)}}
+impl Pretty for u16 {fn to_doc(&self, p:&Printer, _:usize) -> Doc {p.atom(self)}}
+impl Pretty for u32 {fn to_doc(&self, p:&Printer, _:usize) -> Doc {p.atom(self)}}
+impl Pretty for u64 {fn to_doc(&self, p:&Printer, _:usize) -> Doc {p.atom(self)}}
+impl Pretty for usize {fn to_doc(&self, p:&Printer, _:usize) -> Doc {p.atom(self)}}
+impl Pretty for f32 {fn to_doc(&self, p:&Printer, _:usize) -> Doc {p.atom(self)}}
+impl Pretty for f64 {fn to_doc(&self, p:&Printer, _:usize) -> Doc {p.atom(self)}}
+impl Pretty for char {fn to_doc(&self, p:&Printer, _:usize) -> Doc {p.atom(self)}}
+impl Pretty for str {fn to_doc(&self, p:&Printer, _:usize) -> Doc {p.atom(self)}}
+impl Pretty for String {fn to_doc(&self, p:&Printer, _:usize) -> Doc {p.atom(self)}}
+
+impl Pretty for Keyword
+{fn to_doc(&self, _:&Printer, _:usize) -> Doc {Doc::text(self.to_string())}}
+
+impl Pretty for Symbol
+{fn to_doc(&self, _:&Printer, _:usize) -> Doc {Doc::text(self.to_string())}}
+
+impl<T> Pretty for &T where T:Pretty + ?Sized
+{fn to_doc(&self, p:&Printer, depth:usize) -> Doc {(**self).to_doc(p, depth)}}
+
+impl<T> Pretty for Box<T> where T:Pretty + ?Sized
+{fn to_doc(&self, p:&Printer, depth:usize) -> Doc {(**self).to_doc(p, depth)}}
+
+impl<T> Pretty for Option<T> where T:Pretty
+{fn to_doc(&self, p:&Printer, depth:usize) -> Doc
+ {match (self, p.syntax)
+  {(Some(t), Syntax::Edn) => t.to_doc(p, depth),
+   (Some(t), Syntax::Debug) => Doc::concat(vec![Doc::text("Some("), t.to_doc(p, depth), Doc::text(")")]),
+   (None, Syntax::Edn) => Doc::text("nil"),
+   (None, Syntax::Debug) => Doc::text("None")}}}
+
+impl<T> Pretty for [T] where T:Pretty
+{fn to_doc(&self, p:&Printer, depth:usize) -> Doc {p.seq_doc(depth, "[", "]", self)}}
+
+impl<T, const N:usize> Pretty for [T;N] where T:Pretty
+{fn to_doc(&self, p:&Printer, depth:usize) -> Doc {p.seq_doc(depth, "[", "]", self)}}
+
+impl<T> Pretty for Vec<T> where T:Pretty
+{fn to_doc(&self, p:&Printer, depth:usize) -> Doc {p.seq_doc(depth, "[", "]", self)}}
+
+impl<T> Pretty for VecDeque<T> where T:Pretty
+{fn to_doc(&self, p:&Printer, depth:usize) -> Doc {p.seq_doc(depth, "[", "]", self)}}
+
+impl<T> Pretty for LinkedList<T> where T:Pretty
+{fn to_doc(&self, p:&Printer, depth:usize) -> Doc {p.seq_doc(depth, "[", "]", self)}}
+
+impl<T> Pretty for PersistentDeque<T> where T:Pretty
+{fn to_doc(&self, p:&Printer, depth:usize) -> Doc {p.seq_doc(depth, "[", "]", self)}}
+
+fn set_open(p:&Printer) -> &'static str
+{match p.syntax {Syntax::Edn => "#{", Syntax::Debug => "{"}}
+
+impl<T,S> Pretty for HashSet<T,S> where T:Pretty + Hash + Eq
+{fn to_doc(&self, p:&Printer, depth:usize) -> Doc {p.sorted_seq_doc(depth, set_open(p), "}", self)}}
+
+impl<T> Pretty for BTreeSet<T> where T:Pretty
+{fn to_doc(&self, p:&Printer, depth:usize) -> Doc {p.seq_doc(depth, set_open(p), "}", self)}}
+
+impl<T> Pretty for VecSortedSet<T> where T:Pretty
+{fn to_doc(&self, p:&Printer, depth:usize) -> Doc {p.seq_doc(depth, set_open(p), "}", self)}}
+
+impl<K,V,S> Pretty for HashMap<K,V,S> where K:Pretty + Hash + Eq, V:Pretty
+{fn to_doc(&self, p:&Printer, depth:usize) -> Doc {p.map_doc(depth, self, true)}}
+
+impl<K,V> Pretty for BTreeMap<K,V> where K:Pretty, V:Pretty
+{fn to_doc(&self, p:&Printer, depth:usize) -> Doc {p.map_doc(depth, self, false)}}
+
+impl<K,V> Pretty for VecSortedMap<K,V> where K:Pretty, V:Pretty
+{fn to_doc(&self, p:&Printer, depth:usize) -> Doc {p.map_doc(depth, self.iter().map(|(k,v)| (k,v)), false)}}
+
+impl Pretty for Value {
+    fn to_doc(&self, p:&Printer, depth:usize) -> Doc {
+        match *self {
+            Value::Nil => Doc::text("nil"),
+            Value::Bool(b) => b.to_doc(p, depth),
+            Value::Int(n) => n.to_doc(p, depth),
+            Value::Float(x) => x.to_doc(p, depth),
+            Value::Char(c) => c.to_doc(p, depth),
+            Value::Str(ref s) => s.to_doc(p, depth),
+            Value::Keyword(k) => k.to_doc(p, depth),
+            Value::Symbol(s) => s.to_doc(p, depth),
+            Value::List(ref l) => match p.syntax
+            {Syntax::Edn => p.seq_doc(depth, "(", ")", l), Syntax::Debug => p.seq_doc(depth, "[", "]", l)},
+            Value::Vector(ref v) => v.to_doc(p, depth),
+            Value::Map(ref m) => m.to_doc(p, depth),
+            Value::Set(ref s) => s.to_doc(p, depth),
+            Value::Tagged(t, ref v) => Doc::concat(vec![Doc::text(format!("#{} ", t)), v.to_doc(p, depth)])
+        }
+    }
+}
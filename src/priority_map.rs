@@ -0,0 +1,248 @@
+use _map::{Map,MapMut};
+use std::collections::{HashMap,BinaryHeap};
+use std::borrow::Borrow;
+use std::hash::Hash;
+use std::cmp::{Ordering,Reverse};
+use std::iter::FromIterator;
+use std::fmt::{Debug,Formatter,Result};
+
+/// a map from keys to priorities which is also a min-priority queue, like
+/// `clojure`'s [`priority-map`](https://github.com/clojure/data.priority-map).
+///
+/// an indexed binary heap: the entries are kept in heap order while a hash map
+/// remembers the position of each key, so that the priority of any key can be
+/// changed in O(log(len)). this is what `BinaryHeap` lacks for algorithms like
+/// dijkstra's. keys are stored twice, hence `K:Clone`.
+///
+/// # example
+/// ```
+/// use protocoll::{Map,MapMut};
+/// use protocoll::map::PriorityMap;
+/// let mut m = PriorityMap::new()
+///     .inc('a', 3).inc('b', 1).inc('c', 2)
+///     .inc('b', 4);
+/// m.update_mut('c', 0, |p| *p -= 2);
+/// assert_eq!(m.peek_min(), Some((&'c', &0)));
+/// assert_eq!(m.iter().map(|(&k,_)| k).collect::<String>(), "cab");
+/// assert_eq!(m.pop_min(), Some(('c', 0)));
+/// assert_eq!(m[&'b'], 4);
+/// ```
+#[derive(Clone)]
+pub struct PriorityMap<K,P> {heap:Vec<(K,P)>, index:HashMap<K,usize>}
+
+impl<K,P> PriorityMap<K,P> where K:Hash+Eq+Clone, P:Ord {
+    pub fn new() -> Self
+    {PriorityMap{heap:Vec::new(), index:HashMap::new()}}
+
+    pub fn with_capacity(n:usize) -> Self
+    {PriorityMap{heap:Vec::with_capacity(n), index:HashMap::with_capacity(n)}}
+
+    pub fn capacity(&self) -> usize
+    {self.heap.capacity()}
+
+    pub fn reserve(&mut self, n:usize)
+    {self.heap.reserve(n); self.index.reserve(n)}
+
+    pub fn shrink_to_fit(&mut self)
+    {self.heap.shrink_to_fit(); self.index.shrink_to_fit()}
+
+    pub fn clear(&mut self)
+    {self.heap.clear(); self.index.clear()}
+
+    /// O(1)
+    pub fn contains_key<Q>(&self, k:&Q) -> bool where K:Borrow<Q>, Q:?Sized+Hash+Eq
+    {self.index.contains_key(k)}
+
+    /// O(1)
+    pub fn get<Q>(&self, k:&Q) -> Option<&P> where K:Borrow<Q>, Q:?Sized+Hash+Eq
+    {self.index.get(k).map(|&i| &self.heap[i].1)}
+
+    /// sets the priority of `k` to `p`, returning the old one. O(log(len)).
+    pub fn insert(&mut self, k:K, p:P) -> Option<P>
+    {match self.index.get(&k).cloned()
+     {Some(i) => {let old = ::std::mem::replace(&mut self.heap[i].1, p);
+                  self.fix(i); Some(old)}
+      None => {let i = self.heap.len();
+               self.index.insert(k.clone(), i);
+               self.heap.push((k,p));
+               self.sift_up(i); None}}}
+
+    /// O(log(len))
+    pub fn remove<Q>(&mut self, k:&Q) -> Option<P> where K:Borrow<Q>, Q:?Sized+Hash+Eq
+    {self.index.get(k).cloned().map(|i| self.remove_at(i).1)}
+
+    /// the entry with the least priority. O(1).
+    pub fn peek_min(&self) -> Option<(&K,&P)>
+    {self.heap.first().map(|(k,p)| (k,p))}
+
+    /// removes the entry with the least priority. O(log(len)).
+    pub fn pop_min(&mut self) -> Option<(K,P)>
+    {if self.heap.is_empty() {None} else {Some(self.remove_at(0))}}
+
+    /// iterate in priority order. O(log(len)) per entry, so taking only the
+    /// first few entries is cheap.
+    pub fn iter(&self) -> Iter<'_,K,P>
+    {let mut front = BinaryHeap::new();
+     if !self.heap.is_empty() {front.push(Reverse(Pos(0, &self.heap)))}
+     Iter{front}}
+
+    fn remove_at(&mut self, i:usize) -> (K,P) {
+        let (k,p) = self.heap.swap_remove(i);
+        self.index.remove(&k);
+        if i < self.heap.len() {
+            self.index.insert(self.heap[i].0.clone(), i);
+            self.fix(i);
+        } (k,p)
+    }
+
+    fn fix(&mut self, i:usize)
+    {let i = self.sift_up(i); self.sift_down(i);}
+
+    fn swap(&mut self, i:usize, j:usize) {
+        self.heap.swap(i,j);
+        *self.index.get_mut(&self.heap[i].0).unwrap() = i;
+        *self.index.get_mut(&self.heap[j].0).unwrap() = j;
+    }
+
+    fn sift_up(&mut self, mut i:usize) -> usize {
+        while i > 0 {
+            let parent = (i - 1) / 2;
+            if self.heap[i].1 >= self.heap[parent].1 {break}
+            self.swap(i,parent); i = parent;
+        } i
+    }
+
+    fn sift_down(&mut self, mut i:usize) -> usize {
+        let l = self.heap.len();
+        loop {
+            let mut least = i;
+            for child in [2 * i + 1, 2 * i + 2].iter().cloned()
+            {if child < l && self.heap[child].1 < self.heap[least].1 {least = child}}
+            if least == i {return i}
+            self.swap(i,least); i = least;
+        }
+    }
+
+    /// restores the heap order after arbitrary changes to the priorities.
+    fn heapify(&mut self)
+    {for i in (0..self.heap.len() / 2).rev() {self.sift_down(i);}}
+}
+
+impl<K,P> PriorityMap<K,P> {
+    pub fn len(&self) -> usize
+    {self.heap.len()}
+
+    pub fn is_empty(&self) -> bool
+    {self.heap.is_empty()}
+}
+
+/// a position in the heap, ordered by the priority there.
+struct Pos<'a,K:'a,P:'a>(usize, &'a [(K,P)]);
+
+impl<'a,K,P> PartialEq for Pos<'a,K,P> where P:Ord
+{fn eq(&self, other:&Self) -> bool {self.cmp(other) == Ordering::Equal}}
+
+impl<'a,K,P> Eq for Pos<'a,K,P> where P:Ord {}
+
+impl<'a,K,P> PartialOrd for Pos<'a,K,P> where P:Ord
+{fn partial_cmp(&self, other:&Self) -> Option<Ordering> {Some(self.cmp(other))}}
+
+impl<'a,K,P> Ord for Pos<'a,K,P> where P:Ord
+{fn cmp(&self, other:&Self) -> Ordering
+ {self.1[self.0].1.cmp(&other.1[other.0].1).then(self.0.cmp(&other.0))}}
+
+/// walks the heap as a tree, always expanding the least position seen so far.
+pub struct Iter<'a,K:'a,P:'a> {front:BinaryHeap<Reverse<Pos<'a,K,P>>>}
+
+impl<'a,K,P> Iterator for Iter<'a,K,P> where P:Ord
+{type Item = (&'a K, &'a P);
+ fn next(&mut self) -> Option<(&'a K, &'a P)>
+ {self.front.pop().map(|Reverse(Pos(i,heap))|
+  {for child in [2 * i + 1, 2 * i + 2].iter().cloned()
+   {if child < heap.len() {self.front.push(Reverse(Pos(child,heap)))}}
+   let (ref k, ref p) = heap[i]; (k,p)})}}
+
+impl<'a,K,P> IntoIterator for &'a PriorityMap<K,P> where K:Hash+Eq+Clone, P:Ord
+{type Item = (&'a K, &'a P); type IntoIter = Iter<'a,K,P>;
+ fn into_iter(self) -> Iter<'a,K,P> {self.iter()}}
+
+/// pops the entries in priority order.
+pub struct IntoIter<K,P>(PriorityMap<K,P>);
+
+impl<K,P> Iterator for IntoIter<K,P> where K:Hash+Eq+Clone, P:Ord
+{type Item = (K,P);
+ fn next(&mut self) -> Option<(K,P)> {self.0.pop_min()}
+ fn size_hint(&self) -> (usize,Option<usize>) {(self.0.len(), Some(self.0.len()))}}
+
+impl<K,P> IntoIterator for PriorityMap<K,P> where K:Hash+Eq+Clone, P:Ord
+{type Item = (K,P); type IntoIter = IntoIter<K,P>;
+ fn into_iter(self) -> IntoIter<K,P> {IntoIter(self)}}
+
+impl<K,P> Default for PriorityMap<K,P> where K:Hash+Eq+Clone, P:Ord
+{fn default() -> Self {PriorityMap::new()}}
+
+impl<K,P> PartialEq for PriorityMap<K,P> where K:Hash+Eq+Clone, P:Ord
+{fn eq(&self, other:&Self) -> bool
+ {self.len() == other.len() && self.heap.iter().all(|(k,p)| other.get(k) == Some(p))}}
+
+impl<K,P> Eq for PriorityMap<K,P> where K:Hash+Eq+Clone, P:Ord {}
+
+impl<K,P> Extend<(K,P)> for PriorityMap<K,P> where K:Hash+Eq+Clone, P:Ord
+{fn extend<I>(&mut self, iter:I) where I:IntoIterator<Item = (K,P)>
+ {for (k,p) in iter {self.insert(k,p);}}}
+
+impl<K,P> FromIterator<(K,P)> for PriorityMap<K,P> where K:Hash+Eq+Clone, P:Ord
+{fn from_iter<I>(iter:I) -> PriorityMap<K,P> where I:IntoIterator<Item = (K,P)>
+ {Map::plus(PriorityMap::new(),iter)}}
+
+impl<K,Q,P> ::std::ops::Index<&Q> for PriorityMap<K,P> where K:Hash+Eq+Clone+Borrow<Q>, Q:?Sized+Hash+Eq, P:Ord
+{type Output = P; fn index(&self, k:&Q) -> &P {self.get(k).expect("no entry found for key")}}
+
+impl<K,P> Debug for PriorityMap<K,P> where K:Hash+Eq+Clone+Debug, P:Ord+Debug
+{fn fmt(&self, fmt: &mut Formatter) -> Result
+ {fmt.debug_map().entries(self.iter()).finish()}}
+
+impl<K,P> Map<K,P> for PriorityMap<K,P> where K:Hash+Eq+Clone, P:Ord {
+    fn fun<'a,Q>(&'a self) -> Box<dyn Fn(&Q) -> Option<&'a P> + 'a> where K:Borrow<Q>, Q:?Sized+Hash+Eq
+    {Box::new(move |k| self.get(k))}
+
+    /// sets the priority of `k`. O(log(len)).
+    fn inc(mut self, k:K, p:P) -> Self
+    {self.insert(k,p); self}
+
+    fn dec<Q>(mut self, k:&Q) -> Self where K:Borrow<Q>, Q:?Sized+Hash+Eq
+    {self.remove(k); self}
+
+    fn zero(mut self) -> Self
+    {self.clear(); self}
+
+    fn shrink(mut self) -> Self
+    {self.shrink_to_fit(); self}
+
+    fn update<F>(mut self, k:K, f:F) -> Self where F:FnOnce(Option<P>) -> P
+    {let p = f(self.remove(&k)); Map::inc(self,k,p)}
+
+    fn update_all<F>(mut self, mut f:F) -> Self
+        where Self:IntoIterator<Item = (K,P)> + FromIterator<(K,P)>, F:FnMut(&K,P) -> P
+    {let heap = ::std::mem::take(&mut self.heap);
+     self.heap = heap.into_iter().map(|(k,p)| {let p = f(&k,p); (k,p)}).collect();
+     self.heapify(); self}
+}
+
+impl<K,P> MapMut<K,P> for PriorityMap<K,P> where K:Hash+Eq+Clone, P:Ord {
+    /// changes the priority of `k` in place. O(log(len)).
+    fn update_mut<F>(&mut self, k:K, fnil:P, f:F) where F:FnOnce(&mut P)
+    {match self.index.get(&k).cloned()
+     {Some(i) => {f(&mut self.heap[i].1); self.fix(i)}
+      None => {let mut p = fnil; f(&mut p); self.insert(k,p);}}}
+
+    /// O(len) for restoring the heap order afterwards.
+    fn update_all_mut<F>(&mut self, mut f:F) where F:FnMut(&K, &mut P)
+    {for (k,p) in &mut self.heap {f(k,p)} self.heapify()}
+
+    fn merge_mut<I,F>(&mut self, coll:I, mut f:F) where I:IntoIterator<Item = (K,P)>, F:FnMut(&mut P, P)
+    {for (k,p) in coll
+     {match self.index.get(&k).cloned()
+      {Some(i) => {f(&mut self.heap[i].1, p); self.fix(i)}
+       None => {self.insert(k,p);}}}}
+}
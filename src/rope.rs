@@ -0,0 +1,396 @@
+use _str::Str;
+use std::rc::Rc;
+use std::borrow::Cow;
+use std::ops::Range;
+use std::cmp::max;
+use std::fmt::{self,Debug,Display,Formatter};
+
+/// the most bytes a leaf grows to by in-place edits.
+const CHUNK:usize = 1024;
+
+#[derive(Clone,Copy,Default)]
+struct Info {bytes:usize, chars:usize, lines:usize, height:usize}
+
+impl Info {
+    fn of(s:&str) -> Info
+    {Info{bytes:s.len(), chars:s.chars().count(), lines:s.bytes().filter(|&b| b == b'\n').count(), height:0}}
+
+    fn join(l:Info, r:Info) -> Info
+    {Info{bytes:l.bytes + r.bytes, chars:l.chars + r.chars, lines:l.lines + r.lines,
+          height:1 + max(l.height, r.height)}}
+}
+
+#[derive(Clone)]
+enum Node {Leaf(String, Info), Branch(Rc<Node>, Rc<Node>, Info)}
+
+use self::Node::{Leaf,Branch};
+
+impl Node {
+    fn info(&self) -> Info
+    {match *self {Leaf(_,i) | Branch(_,_,i) => i}}
+
+    fn height(&self) -> usize
+    {self.info().height}
+}
+
+fn leaf(s:String) -> Rc<Node>
+{let i = Info::of(&s); Rc::new(Leaf(s,i))}
+
+fn branch(l:Rc<Node>, r:Rc<Node>) -> Rc<Node>
+{let i = Info::join(l.info(), r.info()); Rc::new(Branch(l,r,i))}
+
+fn children(n:&Rc<Node>) -> (Rc<Node>,Rc<Node>)
+{match **n {Branch(ref l, ref r, _) => (l.clone(), r.clone()), Leaf(..) => unreachable!()}}
+
+fn rotate_left(n:Rc<Node>) -> Rc<Node>
+{let (a,bc) = children(&n); let (b,c) = children(&bc); branch(branch(a,b),c)}
+
+fn rotate_right(n:Rc<Node>) -> Rc<Node>
+{let (ab,c) = children(&n); let (a,b) = children(&ab); branch(a,branch(b,c))}
+
+/// concatenates two balanced trees into a balanced tree, in O(log(len)).
+fn join(l:Rc<Node>, r:Rc<Node>) -> Rc<Node> {
+    let (hl,hr) = (l.height(), r.height());
+    if hl > hr + 1 {
+        let (a,c) = children(&l);
+        let t = join(c,r);
+        if t.height() <= a.height() + 1 {branch(a,t)}
+        else {let (tl,tr) = children(&t);
+              let t = if tl.height() > tr.height() {rotate_right(t)} else {t};
+              rotate_left(branch(a,t))}
+    } else if hr > hl + 1 {
+        let (c,a) = children(&r);
+        let t = join(l,c);
+        if t.height() <= a.height() + 1 {branch(t,a)}
+        else {let (tl,tr) = children(&t);
+              let t = if tr.height() > tl.height() {rotate_left(t)} else {t};
+              rotate_right(branch(t,a))}
+    } else {branch(l,r)}
+}
+
+fn join_opt(l:Option<Rc<Node>>, r:Option<Rc<Node>>) -> Option<Rc<Node>>
+{match (l,r) {(Some(l),Some(r)) => Some(join(l,r)), (l,None) => l, (None,r) => r}}
+
+/// splits a tree at byte `i`.
+fn split(n:&Rc<Node>, i:usize) -> (Option<Rc<Node>>,Option<Rc<Node>>) {
+    if i == 0 {return (None, Some(n.clone()))}
+    if i >= n.info().bytes {return (Some(n.clone()), None)}
+    match **n {
+        Leaf(ref s, _) => (Some(leaf(s[..i].to_owned())), Some(leaf(s[i..].to_owned()))),
+        Branch(ref l, ref r, _) => {
+            let lb = l.info().bytes;
+            if i <= lb {let (a,b) = split(l,i); (a, join_opt(b, Some(r.clone())))}
+            else {let (a,b) = split(r, i - lb); (join_opt(Some(l.clone()), a), b)}
+        }
+    }
+}
+
+/// a balanced tree from `s`, cut into leaves of at most `CHUNK` bytes.
+fn build(s:&str) -> Option<Rc<Node>> {
+    let mut leaves = Vec::with_capacity(s.len() / CHUNK + 1);
+    let mut rest = s;
+    while !rest.is_empty() {
+        let mut i = rest.len().min(CHUNK);
+        while !rest.is_char_boundary(i) {i -= 1}
+        leaves.push(leaf(rest[..i].to_owned())); rest = &rest[i..];
+    }
+    fn balanced(ns:&[Rc<Node>]) -> Option<Rc<Node>>
+    {match ns.len()
+     {0 => None, 1 => Some(ns[0].clone()),
+      n => join_opt(balanced(&ns[..n/2]), balanced(&ns[n/2..]))}}
+    balanced(&leaves)
+}
+
+/// the offset and length of the leaf holding byte `i`. at the border of two
+/// leaves, `left` picks the one on the left.
+fn locate(n:&Node, i:usize, left:bool) -> (usize,usize)
+{match *n
+ {Leaf(ref s, _) => (0, s.len()),
+  Branch(ref l, ref r, _) =>
+  {let lb = l.info().bytes;
+   if i < lb || (left && i == lb) {locate(l,i,left)}
+   else {let (o,len) = locate(r, i - lb, left); (lb + o, len)}}}}
+
+fn is_char_boundary(n:&Node, i:usize) -> bool
+{match *n
+ {Leaf(ref s, _) => s.is_char_boundary(i),
+  Branch(ref l, ref r, _) =>
+  {let lb = l.info().bytes;
+   if i <= lb {is_char_boundary(l,i)} else {is_char_boundary(r, i - lb)}}}}
+
+/// replaces bytes `a..b` by `s` in place, within the leaf chosen by `locate`.
+fn edit(n:&mut Rc<Node>, a:usize, b:usize, s:&str, left:bool) {
+    match *Rc::make_mut(n) {
+        Leaf(ref mut t, ref mut i) => {t.replace_range(a..b, s); *i = Info::of(t)}
+        Branch(ref mut l, ref mut r, ref mut i) => {
+            let lb = l.info().bytes;
+            if a < lb || (left && a == lb) {edit(l,a,b,s,left)}
+            else {edit(r, a - lb, b - lb, s, left)}
+            *i = Info::join(l.info(), r.info());
+        }
+    }
+}
+
+/// a rope: a string stored as a balanced tree of chunks.
+///
+/// inserting and removing anywhere take O(log(len)) plus the length of the
+/// edit, instead of O(len) for `String`. cloning is O(1) since the chunks are
+/// shared; an edit copies only the path to the chunks it touches, so keeping
+/// old clones around as undo snapshots is cheap.
+///
+/// all offsets are in bytes and must lie on char boundaries, unless stated
+/// otherwise. lines are separated by `\n`.
+///
+/// # example
+/// ```
+/// use protocoll::Str;
+/// use protocoll::string::Rope;
+/// let mut r = Rope::from("hello\nworld").plus("!");
+/// let undo = r.clone();
+/// r.insert(5, ", big");
+/// r.remove(0..1);
+/// r.insert(0, "H");
+/// assert_eq!(r.to_string(), "Hello, big\nworld!");
+/// assert_eq!(r.line(1), "world!");
+/// assert_eq!(undo.to_string(), "hello\nworld!");
+/// ```
+#[derive(Clone,Default)]
+pub struct Rope {root:Option<Rc<Node>>}
+
+impl Rope {
+    pub fn new() -> Self
+    {Rope{root:None}}
+
+    fn info(&self) -> Info
+    {self.root.as_ref().map_or(Info::default(), |n| n.info())}
+
+    /// O(1)
+    pub fn len(&self) -> usize
+    {self.info().bytes}
+
+    pub fn is_empty(&self) -> bool
+    {self.len() == 0}
+
+    /// O(1)
+    pub fn len_chars(&self) -> usize
+    {self.info().chars}
+
+    /// O(1). a trailing `\n` starts an empty last line.
+    pub fn len_lines(&self) -> usize
+    {self.info().lines + 1}
+
+    pub fn clear(&mut self)
+    {self.root = None}
+
+    /// inserts `s` at byte `i`. O(log(len) + len(s)).
+    pub fn insert(&mut self, i:usize, s:&str) {
+        assert!(i <= self.len(), "byte index {} out of bounds", i);
+        assert!(self.root.as_ref().is_none_or(|n| is_char_boundary(n,i)), "byte index {} is not a char boundary", i);
+        if s.is_empty() {return}
+        match self.root.take() {
+            None => self.root = build(s),
+            Some(mut n) => {
+                if locate(&n, i, true).1 + s.len() <= CHUNK
+                {edit(&mut n, i, i, s, true); self.root = Some(n)}
+                else {let (a,b) = split(&n,i); self.root = join_opt(join_opt(a, build(s)), b)}
+            }
+        }
+    }
+
+    /// removes the bytes in `r`. O(log(len)) plus the length of `r` when it
+    /// lies within a single chunk.
+    pub fn remove(&mut self, r:Range<usize>) {
+        assert!(r.start <= r.end && r.end <= self.len(), "byte range {:?} out of bounds", r);
+        assert!(self.root.as_ref().is_none_or(|n| is_char_boundary(n, r.start) && is_char_boundary(n, r.end)),
+                "byte range {:?} is not on char boundaries", r);
+        if r.start == r.end {return}
+        if let Some(mut n) = self.root.take() {
+            let (o,len) = locate(&n, r.start, false);
+            if r.end <= o + len && r.end - r.start < len
+            {edit(&mut n, r.start, r.end, "", false); self.root = Some(n)}
+            else {let (a,_) = split(&n, r.start); let (_,b) = split(&n, r.end);
+                  self.root = join_opt(a,b)}
+        }
+    }
+
+    /// inserts `s` at char `c`.
+    pub fn insert_at_char(&mut self, c:usize, s:&str)
+    {let i = self.char_to_byte(c); self.insert(i,s)}
+
+    /// removes the chars in `r`.
+    pub fn remove_chars(&mut self, r:Range<usize>)
+    {let a = self.char_to_byte(r.start); let b = self.char_to_byte(r.end); self.remove(a..b)}
+
+    /// the byte offset of char `c`. O(log(len)).
+    pub fn char_to_byte(&self, c:usize) -> usize {
+        assert!(c <= self.len_chars(), "char index {} out of bounds", c);
+        let (mut n, mut c, mut b) = (self.root.as_ref(), c, 0);
+        while let Some(node) = n {
+            match **node {
+                Leaf(ref s, _) => return b + s.char_indices().nth(c).map_or(s.len(), |(i,_)| i),
+                Branch(ref l, ref r, _) => {
+                    let i = l.info();
+                    if c < i.chars {n = Some(l)} else {c -= i.chars; b += i.bytes; n = Some(r)}
+                }
+            }
+        } b
+    }
+
+    /// the number of chars before byte `i`. O(log(len)).
+    pub fn byte_to_char(&self, i:usize) -> usize {
+        assert!(i <= self.len(), "byte index {} out of bounds", i);
+        let (mut n, mut i, mut c) = (self.root.as_ref(), i, 0);
+        while let Some(node) = n {
+            match **node {
+                Leaf(ref s, _) => return c + s[..i].chars().count(),
+                Branch(ref l, ref r, _) => {
+                    let info = l.info();
+                    if i < info.bytes {n = Some(l)} else {i -= info.bytes; c += info.chars; n = Some(r)}
+                }
+            }
+        } c
+    }
+
+    /// the byte offset where line `l` starts. O(log(len)).
+    pub fn line_to_byte(&self, l:usize) -> usize {
+        assert!(l < self.len_lines(), "line index {} out of bounds", l);
+        let (mut n, mut l, mut b) = (self.root.as_ref(), l, 0);
+        while let Some(node) = n {
+            if l == 0 {break}
+            match **node {
+                Leaf(ref s, _) => return b + s.match_indices('\n').nth(l - 1).map_or(s.len(), |(i,_)| i + 1),
+                Branch(ref left, ref right, _) => {
+                    let info = left.info();
+                    if l <= info.lines {n = Some(left)} else {l -= info.lines; b += info.bytes; n = Some(right)}
+                }
+            }
+        } b
+    }
+
+    /// the line holding byte `i`. O(log(len)).
+    pub fn byte_to_line(&self, i:usize) -> usize {
+        assert!(i <= self.len(), "byte index {} out of bounds", i);
+        let (mut n, mut i, mut l) = (self.root.as_ref(), i, 0);
+        while let Some(node) = n {
+            match **node {
+                Leaf(ref s, _) => return l + s[..i].bytes().filter(|&b| b == b'\n').count(),
+                Branch(ref left, ref right, _) => {
+                    let info = left.info();
+                    if i < info.bytes {n = Some(left)} else {i -= info.bytes; l += info.lines; n = Some(right)}
+                }
+            }
+        } l
+    }
+
+    /// line `l` without its `\n`.
+    pub fn line(&self, l:usize) -> Cow<'_,str>
+    {let a = self.line_to_byte(l);
+     let b = if l + 1 < self.len_lines() {self.line_to_byte(l + 1) - 1} else {self.len()};
+     self.slice(a..b)}
+
+    /// the bytes in `r`, borrowed when they lie within a single chunk.
+    pub fn slice(&self, r:Range<usize>) -> Cow<'_,str> {
+        assert!(r.start <= r.end && r.end <= self.len(), "byte range {:?} out of bounds", r);
+        if r.start == r.end {return Cow::Borrowed("")}
+        let (mut o, chunks) = self.chunks_at(r.start);
+        let mut out = String::with_capacity(r.end - r.start);
+        for c in chunks {
+            let end = o + c.len();
+            if out.is_empty() && r.end <= end {return Cow::Borrowed(&c[r.start - o..r.end - o])}
+            out.push_str(&c[r.start.max(o) - o..r.end.min(end) - o]);
+            o = end;
+            if o >= r.end {break}
+        }
+        Cow::Owned(out)
+    }
+
+    /// iterate over the chunks in order.
+    pub fn chunks(&self) -> Chunks<'_>
+    {Chunks{stack:self.root.as_deref().into_iter().collect()}}
+
+    /// iterate over the chunks starting from the one holding byte `i`, along
+    /// with the offset of that chunk. O(log(len)) to get started.
+    pub fn chunks_at(&self, i:usize) -> (usize,Chunks<'_>) {
+        let mut stack = Vec::new();
+        let (mut n, mut i, mut o) = (self.root.as_deref(), i, 0);
+        while let Some(node) = n {
+            match *node {
+                Leaf(..) => {stack.push(node); break}
+                Branch(ref l, ref r, _) => {
+                    let lb = l.info().bytes;
+                    if i < lb {stack.push(r); n = Some(l)} else {i -= lb; o += lb; n = Some(r)}
+                }
+            }
+        }
+        (o, Chunks{stack})
+    }
+
+    pub fn chars(&self) -> impl Iterator<Item = char> + '_
+    {self.chunks().flat_map(str::chars)}
+
+    pub fn bytes(&self) -> impl Iterator<Item = u8> + '_
+    {self.chunks().flat_map(str::bytes)}
+
+    /// the last char.
+    fn last_char(&self) -> Option<char>
+    {let mut n = self.root.as_ref();
+     while let Some(node) = n
+     {match **node
+      {Leaf(ref s, _) => return s.chars().next_back(),
+       Branch(_, ref r, _) => n = Some(r)}}
+     None}
+}
+
+pub struct Chunks<'a> {stack:Vec<&'a Node>}
+
+impl<'a> Iterator for Chunks<'a>
+{type Item = &'a str;
+ fn next(&mut self) -> Option<&'a str>
+ {while let Some(n) = self.stack.pop()
+  {match *n
+   {Leaf(ref s, _) => return Some(s),
+    Branch(ref l, ref r, _) => {self.stack.push(r); self.stack.push(l)}}}
+  None}}
+
+impl From<&str> for Rope
+{fn from(s:&str) -> Rope {Rope{root:build(s)}}}
+
+impl From<String> for Rope
+{fn from(s:String) -> Rope {Rope::from(s.as_str())}}
+
+impl Display for Rope
+{fn fmt(&self, fmt:&mut Formatter) -> fmt::Result
+ {for c in self.chunks() {fmt.write_str(c)?} Ok(())}}
+
+impl Debug for Rope
+{fn fmt(&self, fmt:&mut Formatter) -> fmt::Result
+ {Debug::fmt(&self.to_string(), fmt)}}
+
+impl PartialEq for Rope
+{fn eq(&self, other:&Rope) -> bool
+ {self.len() == other.len() && self.bytes().eq(other.bytes())}}
+
+impl Eq for Rope {}
+
+impl PartialEq<&str> for Rope
+{fn eq(&self, other:&&str) -> bool
+ {self.len() == other.len() && self.bytes().eq(other.bytes())}}
+
+impl Str for Rope {
+    fn inc(self, c:char) -> Self
+    {self.plus(c.encode_utf8(&mut [0;4]))}
+
+    fn dec(mut self) -> Self
+    {if let Some(c) = self.last_char()
+     {let l = self.len(); self.remove(l - c.len_utf8()..l)} self}
+
+    fn plus(mut self, s:&str) -> Self
+    {let l = self.len(); self.insert(l,s); self}
+
+    fn zero(mut self) -> Self
+    {self.clear(); self}
+
+    /// repacks the chunks, which may have become fragmented by edits. O(len).
+    fn shrink(self) -> Self
+    {Rope::from(self.to_string())}
+}
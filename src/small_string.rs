@@ -0,0 +1,171 @@
+use _str::Str;
+use _text::Text;
+use std::borrow::Borrow;
+use std::ops::Deref;
+use std::hash::{Hash,Hasher};
+use std::cmp::Ordering;
+use std::iter::FromIterator;
+use std::fmt::{self,Debug,Display,Formatter};
+
+/// the most bytes a [`SmallString`](struct.SmallString.html) keeps inline.
+pub const INLINE:usize = 23;
+
+#[derive(Clone)]
+enum Repr {Inline(u8, [u8;INLINE]), Heap(String)}
+
+/// a string which keeps up to 23 bytes inline and only spills to the heap
+/// beyond that.
+///
+/// derefs to `str`, and hashes, compares and borrows like one, so it can key a
+/// [`VecSortedMap`](../map/struct.VecSortedMap.html) or a `HashMap` which is
+/// then looked up by `&str`.
+///
+/// # example
+/// ```
+/// use protocoll::{Map,Str};
+/// use protocoll::map::VecSortedMap;
+/// use protocoll::string::SmallString;
+/// let k = SmallString::new().plus("user").inc('_').plus("id");
+/// assert!(k.is_inline());
+/// let m = VecSortedMap::new().inc(k, 42);
+/// assert_eq!(m["user_id"], 42);
+/// ```
+#[derive(Clone)]
+pub struct SmallString(Repr);
+
+impl SmallString {
+    pub fn new() -> Self
+    {SmallString(Repr::Inline(0, [0;INLINE]))}
+
+    pub fn as_str(&self) -> &str
+    {match self.0
+     {Repr::Inline(l, ref b) => unsafe {::std::str::from_utf8_unchecked(&b[..l as usize])},
+      Repr::Heap(ref s) => s}}
+
+    /// whether the content is still stored inline.
+    pub fn is_inline(&self) -> bool
+    {match self.0 {Repr::Inline(..) => true, Repr::Heap(_) => false}}
+
+    pub fn capacity(&self) -> usize
+    {match self.0 {Repr::Inline(..) => INLINE, Repr::Heap(ref s) => s.capacity()}}
+
+    pub fn push_str(&mut self, s:&str) {
+        match self.0 {
+            Repr::Inline(ref mut l, ref mut b) if *l as usize + s.len() <= INLINE => {
+                let n = *l as usize;
+                b[n..n + s.len()].copy_from_slice(s.as_bytes());
+                *l += s.len() as u8; return
+            }
+            Repr::Heap(ref mut h) => {h.push_str(s); return}
+            _ => ()
+        }
+        let mut h = String::with_capacity(self.len() + s.len());
+        h.push_str(self.as_str()); h.push_str(s);
+        self.0 = Repr::Heap(h)
+    }
+
+    pub fn push(&mut self, c:char)
+    {self.push_str(c.encode_utf8(&mut [0;4]))}
+
+    pub fn pop(&mut self) -> Option<char>
+    {let c = self.as_str().chars().next_back()?;
+     let l = self.len() - c.len_utf8();
+     self.truncate(l); Some(c)}
+
+    /// panics if `n` is not on a char boundary.
+    pub fn truncate(&mut self, n:usize)
+    {match self.0
+     {Repr::Inline(ref mut l, ref b) =>
+      {if n < *l as usize
+       {assert!(unsafe {::std::str::from_utf8_unchecked(&b[..*l as usize])}.is_char_boundary(n));
+        *l = n as u8}}
+      Repr::Heap(ref mut h) => h.truncate(n)}}
+
+    pub fn clear(&mut self)
+    {self.truncate(0)}
+
+    /// moves the content back inline if it fits.
+    pub fn shrink_to_fit(&mut self)
+    {let inline = match self.0
+     {Repr::Heap(ref mut h) if h.len() <= INLINE => SmallString::from(h.as_str()),
+      Repr::Heap(ref mut h) => {h.shrink_to_fit(); return}
+      Repr::Inline(..) => return};
+     *self = inline}
+}
+
+impl Default for SmallString
+{fn default() -> Self {SmallString::new()}}
+
+impl Deref for SmallString
+{type Target = str; fn deref(&self) -> &str {self.as_str()}}
+
+impl AsRef<str> for SmallString
+{fn as_ref(&self) -> &str {self.as_str()}}
+
+impl Borrow<str> for SmallString
+{fn borrow(&self) -> &str {self.as_str()}}
+
+impl From<&str> for SmallString
+{fn from(s:&str) -> SmallString {let mut t = SmallString::new(); t.push_str(s); t}}
+
+/// keeps the heap allocation of a long string, and moves a short one inline.
+impl From<String> for SmallString
+{fn from(s:String) -> SmallString
+ {if s.len() <= INLINE {SmallString::from(s.as_str())} else {SmallString(Repr::Heap(s))}}}
+
+impl From<SmallString> for String
+{fn from(s:SmallString) -> String
+ {match s.0 {Repr::Heap(h) => h, Repr::Inline(..) => s.as_str().to_owned()}}}
+
+impl FromIterator<char> for SmallString
+{fn from_iter<I>(iter:I) -> SmallString where I:IntoIterator<Item = char>
+ {iter.into_iter().fold(SmallString::new(), Str::inc)}}
+
+impl<'a> FromIterator<&'a str> for SmallString
+{fn from_iter<I>(iter:I) -> SmallString where I:IntoIterator<Item = &'a str>
+ {iter.into_iter().fold(SmallString::new(), Str::plus)}}
+
+impl PartialEq for SmallString
+{fn eq(&self, other:&SmallString) -> bool {self.as_str() == other.as_str()}}
+
+impl Eq for SmallString {}
+
+impl PartialEq<str> for SmallString
+{fn eq(&self, other:&str) -> bool {self.as_str() == other}}
+
+impl PartialEq<&str> for SmallString
+{fn eq(&self, other:&&str) -> bool {self.as_str() == *other}}
+
+impl PartialOrd for SmallString
+{fn partial_cmp(&self, other:&SmallString) -> Option<Ordering> {Some(self.cmp(other))}}
+
+impl Ord for SmallString
+{fn cmp(&self, other:&SmallString) -> Ordering {self.as_str().cmp(other.as_str())}}
+
+impl Hash for SmallString
+{fn hash<H:Hasher>(&self, state:&mut H) {self.as_str().hash(state)}}
+
+impl Display for SmallString
+{fn fmt(&self, fmt:&mut Formatter) -> fmt::Result {Display::fmt(self.as_str(), fmt)}}
+
+impl Debug for SmallString
+{fn fmt(&self, fmt:&mut Formatter) -> fmt::Result {Debug::fmt(self.as_str(), fmt)}}
+
+impl Str for SmallString {
+    fn inc(mut self, c:char) -> Self
+    {self.push(c); self}
+
+    fn dec(mut self) -> Self
+    {self.pop(); self}
+
+    fn plus(mut self, s:&str) -> Self
+    {self.push_str(s); self}
+
+    fn zero(mut self) -> Self
+    {self.clear(); self}
+
+    fn shrink(mut self) -> Self
+    {self.shrink_to_fit(); self}
+}
+
+impl Text for SmallString {}
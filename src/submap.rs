@@ -0,0 +1,162 @@
+use _map::{Map,MapMut};
+use _seq::Seq;
+use vec_sorted_map::VecSortedMap;
+use std::collections::{HashMap,BTreeMap};
+use std::borrow::Borrow;
+use std::hash::Hash;
+use std::mem;
+
+/// maps which can be narrowed down to some of their entries, in place.
+///
+/// # example
+/// ```
+/// use protocoll::map::{SubMap,VecSortedMap};
+/// use std::collections::{HashMap,HashSet};
+/// let m:HashMap<_,_> = vec![("a",1), ("b",2), ("c",3)].into_iter().collect();
+/// let ks:HashSet<_> = vec!["a", "c", "z"].into_iter().collect();
+/// let s = m.clone().select_keys(&ks);
+/// assert_eq!(s.len(), 2);
+/// assert_eq!(m.clone().select_keys(vec!["b"]).get("b"), Some(&2));
+/// let v:VecSortedMap<_,_> = (0..10).map(|n| (n, n * n)).collect();
+/// let v = v.filter_keys(|&k| k % 2 == 0).filter_vals(|&v| v > 10);
+/// assert_eq!(v.view_content(), &[(4,16), (6,36), (8,64)]);
+/// ```
+pub trait SubMap<K,V> where Self:Sized {
+    /// keeps the entries with the keys in `keys`, which may be a set or any
+    /// other collection.
+    ///
+    /// like `clojure`'s [`select-keys`](http://clojuredocs.org/clojure.core/select-keys).
+    fn select_keys<I>(self, keys:I) -> Self where I:IntoIterator, I::Item:Borrow<K>;
+
+    /// keeps the entries whose keys satisfy `p`.
+    fn filter_keys<P>(self, p:P) -> Self where P:FnMut(&K) -> bool;
+
+    /// keeps the entries whose values satisfy `p`.
+    fn filter_vals<P>(self, p:P) -> Self where P:FnMut(&V) -> bool;
+}
+
+impl<K,V> SubMap<K,V> for HashMap<K,V> where K:Hash+Eq {
+    fn select_keys<I>(mut self, keys:I) -> Self where I:IntoIterator, I::Item:Borrow<K>
+    {let mut m = HashMap::new();
+     for k in keys {if let Some((k,v)) = self.remove_entry(k.borrow()) {m.insert(k,v);}}
+     m}
+
+    fn filter_keys<P>(mut self, mut p:P) -> Self where P:FnMut(&K) -> bool
+    {self.retain(|k,_| p(k)); self}
+
+    fn filter_vals<P>(mut self, mut p:P) -> Self where P:FnMut(&V) -> bool
+    {self.retain(|_,v| p(v)); self}
+}
+
+impl<K,V> SubMap<K,V> for BTreeMap<K,V> where K:Ord {
+    fn select_keys<I>(mut self, keys:I) -> Self where I:IntoIterator, I::Item:Borrow<K>
+    {let mut m = BTreeMap::new();
+     for k in keys {if let Some((k,v)) = self.remove_entry(k.borrow()) {m.insert(k,v);}}
+     m}
+
+    fn filter_keys<P>(mut self, mut p:P) -> Self where P:FnMut(&K) -> bool
+    {self.retain(|k,_| p(k)); self}
+
+    fn filter_vals<P>(mut self, mut p:P) -> Self where P:FnMut(&V) -> bool
+    {self.retain(|_,v| p(v)); self}
+}
+
+/// all in place, keeping the order.
+impl<K,V> SubMap<K,V> for VecSortedMap<K,V> where K:Ord {
+    fn select_keys<I>(mut self, keys:I) -> Self where I:IntoIterator, I::Item:Borrow<K>
+    {let mut keep = vec![false; self.len()];
+     {let es = self.view_content();
+      for k in keys {if let Ok(i) = es.binary_search_by(|e| e.0.cmp(k.borrow())) {keep[i] = true}}}
+     let mut i = 0;
+     self.retain(|_| {i += 1; keep[i - 1]});
+     self}
+
+    fn filter_keys<P>(mut self, mut p:P) -> Self where P:FnMut(&K) -> bool
+    {self.retain(|e| p(&e.0)); self}
+
+    fn filter_vals<P>(mut self, mut p:P) -> Self where P:FnMut(&V) -> bool
+    {self.retain(|e| p(&e.1)); self}
+}
+
+/// resolves a collision by keeping the `old` value.
+pub fn keep_first<V>(old:V, _new:V) -> V
+{old}
+
+/// resolves a collision by keeping the `new` value.
+pub fn keep_last<V>(_old:V, new:V) -> V
+{new}
+
+/// the map with keys transformed by `f`, into another map. when two keys
+/// collide, their values are resolved by `collide(old, new)` in the order of
+/// `m`, e.g. by [`keep_first`](fn.keep_first.html),
+/// [`keep_last`](fn.keep_last.html), or a merge.
+///
+/// # example
+/// ```
+/// use protocoll::map::{self,VecSortedMap};
+/// use std::collections::BTreeMap;
+/// use std::ops::Add;
+/// let m:VecSortedMap<_,_> = vec![("a",1), ("B",2), ("b",3)].into_iter().collect();
+/// let f = |k:&&str| k.to_lowercase();
+/// let first:BTreeMap<_,_> = map::map_keys(f, map::keep_first, m.clone());
+/// assert_eq!(first["b"], 2);
+/// let sum:BTreeMap<_,_> = map::map_keys(f, i32::add, m);
+/// assert_eq!(sum["b"], 5);
+/// ```
+pub fn map_keys<M,N,K,V,J,F,C>(mut f:F, collide:C, m:M) -> N
+    where M:IntoIterator<Item = (K,V)>, F:FnMut(&K) -> J, C:FnMut(V,V) -> V, N:Map<J,V> + Default
+{N::default().merge(m.into_iter().map(|(k,v)| (f(&k), v)), collide)}
+
+/// the map with values transformed by `f`, into another map.
+///
+/// like `clojure`'s [`update-vals`](http://clojuredocs.org/clojure.core/update-vals).
+///
+/// # example
+/// ```
+/// use protocoll::map::{self,VecSortedMap};
+/// let m:VecSortedMap<_,_> = vec![(1,"a"), (2,"bb")].into_iter().collect();
+/// let n:VecSortedMap<_,_> = map::map_vals(str::len, m);
+/// assert_eq!(n.view_content(), &[(1,1), (2,2)]);
+/// ```
+pub fn map_vals<M,N,K,V,W,F>(mut f:F, m:M) -> N
+    where M:IntoIterator<Item = (K,V)>, F:FnMut(V) -> W, N:Map<K,W> + Default
+{N::default().plus(m.into_iter().map(|(k,v)| (k, f(v))))}
+
+/// the map from values to keys; when values repeat, the last key in the
+/// order of `m` wins.
+///
+/// like `clojure`'s [`map-invert`](http://clojuredocs.org/clojure.set/map-invert).
+///
+/// # example
+/// ```
+/// use protocoll::map::{self,VecSortedMap};
+/// use std::collections::BTreeMap;
+/// let m:VecSortedMap<_,_> = vec![("a",1), ("b",2), ("c",1)].into_iter().collect();
+/// let i:BTreeMap<_,_> = map::invert(m.clone());
+/// assert_eq!(i[&1], "c");
+/// let i:BTreeMap<_,Vec<_>> = map::invert_multi(m);
+/// assert_eq!(i[&1], ["a","c"]);
+/// ```
+pub fn invert<M,N,K,V>(m:M) -> N where M:IntoIterator<Item = (K,V)>, N:Map<V,K> + Default
+{N::default().plus(m.into_iter().map(|(k,v)| (v,k)))}
+
+/// the map from values to the seqs of their keys, in the order of `m`.
+pub fn invert_multi<M,N,K,V,S>(m:M) -> N where M:IntoIterator<Item = (K,V)>, N:MapMut<V,S> + Default, S:Seq<K> + Default
+{let mut n = N::default();
+ for (k,v) in m {n.update_mut(v, S::default(), |s| *s = mem::take(s).inc(k))}
+ n}
+
+/// the map from `keys` to `vals`, pairing them up until either runs out; the
+/// last of repeated keys wins.
+///
+/// like `clojure`'s [`zipmap`](http://clojuredocs.org/clojure.core/zipmap).
+///
+/// # example
+/// ```
+/// use protocoll::map;
+/// use std::collections::HashMap;
+/// let m:HashMap<_,_> = map::zipmap(vec!['a','b','c'], 1..);
+/// assert_eq!(m[&'c'], 3);
+/// ```
+pub fn zipmap<N,I,J>(keys:I, vals:J) -> N where I:IntoIterator, J:IntoIterator, N:Map<I::Item,J::Item> + Default
+{N::default().plus(keys.into_iter().zip(vals))}
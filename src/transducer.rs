@@ -0,0 +1,403 @@
+use _seq::Seq;
+use _set::Set;
+use _map::Map as MapProtocol;
+use std::collections::HashSet;
+use std::hash::Hash;
+use std::marker::PhantomData;
+use std::mem;
+
+/// an accumulator wrapped to stop a reduction early.
+///
+/// steps return `Err(Reduced(acc))` to stop, so `?` passes it on.
+///
+/// like `clojure`'s [`reduced`](http://clojuredocs.org/clojure.core/reduced).
+#[derive(Clone,Copy,PartialEq,Eq,Debug)]
+pub struct Reduced<A>(pub A);
+
+/// a reducing function, stepping an accumulator `A` with items `T`.
+///
+/// closures `FnMut(A,T) -> Result<A,Reduced<A>>` are reducers which need no
+/// completion.
+pub trait Reducer<A,T> {
+    /// adds `t` to `acc`, or stops with `Err(Reduced(acc))`.
+    fn step(&mut self, acc:A, t:T) -> Result<A,Reduced<A>>;
+
+    /// finishes the reduction, flushing any pending state into `acc`.
+    fn complete(&mut self, acc:A) -> A;
+}
+
+impl<A,T,F> Reducer<A,T> for F where F:FnMut(A,T) -> Result<A,Reduced<A>> {
+    fn step(&mut self, acc:A, t:T) -> Result<A,Reduced<A>>
+    {self(acc, t)}
+
+    fn complete(&mut self, acc:A) -> A
+    {acc}
+}
+
+/// a transformation of reducers, independent of where the items come from
+/// and where they go.
+///
+/// tuples of transducers compose them: in `(a,b)`, `a` sees the items first
+/// and passes what it makes to `b`, like `clojure`'s `(comp a b)`.
+pub trait Transducer<R> {
+    type Reducer;
+    /// wraps the reducer `r`.
+    fn apply(self, r:R) -> Self::Reducer;
+}
+
+/// composes two transducers, `a` first. the same as the tuple `(a,b)`.
+///
+/// like `clojure`'s [`comp`](http://clojuredocs.org/clojure.core/comp) on
+/// transducers.
+pub fn comp<X,Y>(a:X, b:Y) -> (X,Y)
+{(a,b)}
+
+impl<R,X,Y> Transducer<R> for (X,Y) where Y:Transducer<R>, X:Transducer<Y::Reducer> {
+    type Reducer = X::Reducer;
+    fn apply(self, r:R) -> X::Reducer
+    {self.0.apply(self.1.apply(r))}
+}
+
+impl<R,X,Y,Z> Transducer<R> for (X,Y,Z) where Z:Transducer<R>, Y:Transducer<Z::Reducer>, X:Transducer<Y::Reducer> {
+    type Reducer = X::Reducer;
+    fn apply(self, r:R) -> X::Reducer
+    {self.0.apply(self.1.apply(self.2.apply(r)))}
+}
+
+impl<R,W,X,Y,Z> Transducer<R> for (W,X,Y,Z)
+    where Z:Transducer<R>, Y:Transducer<Z::Reducer>, X:Transducer<Y::Reducer>, W:Transducer<X::Reducer> {
+    type Reducer = W::Reducer;
+    fn apply(self, r:R) -> W::Reducer
+    {self.0.apply(self.1.apply(self.2.apply(self.3.apply(r))))}
+}
+
+/// reduces `coll` with `xform` applied to `rf`, starting from `init`.
+///
+/// like `clojure`'s [`transduce`](http://clojuredocs.org/clojure.core/transduce).
+///
+/// # example
+/// ```
+/// use protocoll::xform::{self,transduce,Reduced};
+/// let sum = transduce((xform::filter(|n:&i32| n % 2 == 1), xform::map(|n| n * n), xform::take(3)),
+///                     |acc, n| Ok::<_,Reduced<_>>(acc + n), 0, 1..);
+/// assert_eq!(sum, 1 + 9 + 25);
+/// let first_big = transduce(xform::keep(|n:u32| n.checked_pow(9)),
+///                           |acc:Option<u32>, n| if n > 1000 {Err(Reduced(Some(n)))} else {Ok(acc)},
+///                           None, 1..);
+/// assert_eq!(first_big, Some(19683));
+/// ```
+pub fn transduce<X,R,A,I>(xform:X, rf:R, init:A, coll:I) -> A
+    where I:IntoIterator, X:Transducer<R>, X::Reducer:Reducer<A,I::Item>
+{let mut r = xform.apply(rf);
+ let mut acc = init;
+ for t in coll
+ {match r.step(acc, t) {Ok(a) => acc = a, Err(Reduced(a)) => {acc = a; break}}}
+ r.complete(acc)}
+
+/// reduces `coll` by `f` from `init`, stopping early when `f` returns
+/// `Err(Reduced(acc))`. it works with any collection which iterates, by value
+/// or by reference.
+///
+/// like `clojure`'s [`reduce`](http://clojuredocs.org/clojure.core/reduce).
+///
+/// # example
+/// ```
+/// use protocoll::set::VecSortedSet;
+/// use protocoll::xform::{reduce,Reduced};
+/// let s:VecSortedSet<i32> = (1..100).collect();
+/// let sum = reduce(|acc, &n| if acc + n > 20 {Err(Reduced(acc))} else {Ok(acc + n)}, 0, &s);
+/// assert_eq!(sum, 15);
+/// ```
+pub fn reduce<A,I,F>(f:F, init:A, coll:I) -> A
+    where I:IntoIterator, F:FnMut(A,I::Item) -> Result<A,Reduced<A>>
+{coll.into_iter().try_fold(init, f).unwrap_or_else(|Reduced(a)| a)}
+
+/// reduces the entries of a map by `f` from `init`, stopping early as in
+/// [`reduce`](fn.reduce.html).
+///
+/// like `clojure`'s [`reduce-kv`](http://clojuredocs.org/clojure.core/reduce-kv).
+///
+/// # example
+/// ```
+/// use protocoll::xform::{reduce_kv,Reduced};
+/// use std::collections::BTreeMap;
+/// let m:BTreeMap<_,_> = vec![("a",1), ("b",-1), ("c",2)].into_iter().collect();
+/// let first_neg = reduce_kv(|acc, k, &v| if v < 0 {Err(Reduced(Some(*k)))} else {Ok(acc)}, None, &m);
+/// assert_eq!(first_neg, Some("b"));
+/// ```
+pub fn reduce_kv<A,I,K,V,F>(mut f:F, init:A, coll:I) -> A
+    where I:IntoIterator<Item = (K,V)>, F:FnMut(A,K,V) -> Result<A,Reduced<A>>
+{coll.into_iter().try_fold(init, |a,(k,v)| f(a, k, v)).unwrap_or_else(|Reduced(a)| a)}
+
+/// the reducer of [`Seq::into_with`](../trait.Seq.html#method.into_with).
+#[derive(Clone,Copy,Debug)]
+pub struct SeqInc;
+
+impl<S,T> Reducer<S,T> for SeqInc where S:Seq<T> {
+    fn step(&mut self, acc:S, t:T) -> Result<S,Reduced<S>>
+    {Ok(Seq::inc(acc, t))}
+
+    fn complete(&mut self, acc:S) -> S
+    {acc}
+}
+
+/// the reducer of [`Set::into_with`](../trait.Set.html#method.into_with).
+#[derive(Clone,Copy,Debug)]
+pub struct SetInc;
+
+impl<S,T> Reducer<S,T> for SetInc where S:Set<T> {
+    fn step(&mut self, acc:S, t:T) -> Result<S,Reduced<S>>
+    {Ok(Set::inc(acc, t))}
+
+    fn complete(&mut self, acc:S) -> S
+    {acc}
+}
+
+/// the reducer of [`Map::into_with`](../trait.Map.html#method.into_with).
+#[derive(Clone,Copy,Debug)]
+pub struct MapInc;
+
+impl<M,K,V> Reducer<M,(K,V)> for MapInc where M:MapProtocol<K,V> {
+    fn step(&mut self, acc:M, (k,v):(K,V)) -> Result<M,Reduced<M>>
+    {Ok(MapProtocol::inc(acc, k, v))}
+
+    fn complete(&mut self, acc:M) -> M
+    {acc}
+}
+
+/// a transducer `X` applied to a reducer `R`.
+pub struct Step<X,R> {xf:X, rf:R}
+
+/// transforms each item by `f`.
+pub fn map<F>(f:F) -> Map<F>
+{Map(f)}
+
+/// transforms each item into many by `f`, and passes them all on.
+pub fn mapcat<F>(f:F) -> Mapcat<F>
+{Mapcat(f)}
+
+/// passes on the items for which `p` holds.
+pub fn filter<P>(p:P) -> Filter<P>
+{Filter(p)}
+
+/// passes on the items for which `p` does not hold.
+pub fn remove<P>(p:P) -> Remove<P>
+{Remove(p)}
+
+/// passes on the `Some`s which `f` returns.
+pub fn keep<F>(f:F) -> Keep<F>
+{Keep(f)}
+
+/// passes on the first `n` items, then stops.
+pub fn take(n:usize) -> Take
+{Take(n)}
+
+/// passes on items while `p` holds, then stops.
+pub fn take_while<P>(p:P) -> TakeWhile<P>
+{TakeWhile(p)}
+
+/// skips the first `n` items.
+pub fn drop(n:usize) -> Drop
+{Drop(n)}
+
+/// passes on vecs of `n` items, and the rest in a shorter one at the end.
+/// panics if `n` is 0.
+pub fn partition_all<T>(n:usize) -> PartitionAll<T>
+{assert!(n > 0, "partition_all needs a positive size"); PartitionAll(n, PhantomData)}
+
+/// skips items equal to the one before.
+pub fn dedupe<T>() -> Dedupe<T>
+{Dedupe(PhantomData)}
+
+/// skips items seen before.
+pub fn distinct<T>() -> Distinct<T>
+{Distinct(PhantomData)}
+
+/// passes on `sep` between the items.
+pub fn interpose<T>(sep:T) -> Interpose<T>
+{Interpose(sep)}
+
+/// passes on the items of each item.
+pub fn cat() -> Cat
+{Cat}
+
+#[derive(Clone,Copy,Debug)] pub struct Map<F>(F);
+#[derive(Clone,Copy,Debug)] pub struct Mapcat<F>(F);
+#[derive(Clone,Copy,Debug)] pub struct Filter<P>(P);
+#[derive(Clone,Copy,Debug)] pub struct Remove<P>(P);
+#[derive(Clone,Copy,Debug)] pub struct Keep<F>(F);
+#[derive(Clone,Copy,Debug)] pub struct Take(usize);
+#[derive(Clone,Copy,Debug)] pub struct TakeWhile<P>(P);
+#[derive(Clone,Copy,Debug)] pub struct Drop(usize);
+#[derive(Clone,Copy,Debug)] pub struct PartitionAll<T>(usize, PhantomData<fn(T)>);
+#[derive(Clone,Copy,Debug)] pub struct Dedupe<T>(PhantomData<fn(T)>);
+#[derive(Clone,Copy,Debug)] pub struct Distinct<T>(PhantomData<fn(T)>);
+#[derive(Clone,Copy,Debug)] pub struct Interpose<T>(T);
+#[derive(Clone,Copy,Debug)] pub struct Cat;
+
+impl<R,F> Transducer<R> for Map<F>
+{type Reducer = Step<Map<F>,R>; fn apply(self, rf:R) -> Self::Reducer {Step{xf:self, rf}}}
+
+impl<R,F> Transducer<R> for Mapcat<F>
+{type Reducer = Step<Mapcat<F>,R>; fn apply(self, rf:R) -> Self::Reducer {Step{xf:self, rf}}}
+
+impl<R,P> Transducer<R> for Filter<P>
+{type Reducer = Step<Filter<P>,R>; fn apply(self, rf:R) -> Self::Reducer {Step{xf:self, rf}}}
+
+impl<R,P> Transducer<R> for Remove<P>
+{type Reducer = Step<Remove<P>,R>; fn apply(self, rf:R) -> Self::Reducer {Step{xf:self, rf}}}
+
+impl<R,F> Transducer<R> for Keep<F>
+{type Reducer = Step<Keep<F>,R>; fn apply(self, rf:R) -> Self::Reducer {Step{xf:self, rf}}}
+
+impl<R> Transducer<R> for Take
+{type Reducer = Step<Take,R>; fn apply(self, rf:R) -> Self::Reducer {Step{xf:self, rf}}}
+
+impl<R,P> Transducer<R> for TakeWhile<P>
+{type Reducer = Step<TakeWhile<P>,R>; fn apply(self, rf:R) -> Self::Reducer {Step{xf:self, rf}}}
+
+impl<R> Transducer<R> for Drop
+{type Reducer = Step<Drop,R>; fn apply(self, rf:R) -> Self::Reducer {Step{xf:self, rf}}}
+
+impl<R,T> Transducer<R> for PartitionAll<T>
+{type Reducer = Partitioning<T,R>; fn apply(self, rf:R) -> Self::Reducer {Partitioning{n:self.0, buf:Vec::new(), rf}}}
+
+impl<R,T> Transducer<R> for Dedupe<T>
+{type Reducer = Deduping<T,R>; fn apply(self, rf:R) -> Self::Reducer {Deduping{prev:None, rf}}}
+
+impl<R,T> Transducer<R> for Distinct<T>
+{type Reducer = Distincting<T,R>; fn apply(self, rf:R) -> Self::Reducer {Distincting{seen:HashSet::new(), rf}}}
+
+impl<R,T> Transducer<R> for Interpose<T>
+{type Reducer = Interposing<T,R>; fn apply(self, rf:R) -> Self::Reducer {Interposing{sep:self.0, started:false, rf}}}
+
+impl<R> Transducer<R> for Cat
+{type Reducer = Step<Cat,R>; fn apply(self, rf:R) -> Self::Reducer {Step{xf:self, rf}}}
+
+impl<A,T,U,F,R> Reducer<A,T> for Step<Map<F>,R> where F:FnMut(T) -> U, R:Reducer<A,U> {
+    fn step(&mut self, acc:A, t:T) -> Result<A,Reduced<A>>
+    {self.rf.step(acc, (self.xf.0)(t))}
+
+    fn complete(&mut self, acc:A) -> A
+    {self.rf.complete(acc)}
+}
+
+impl<A,T,I,F,R> Reducer<A,T> for Step<Mapcat<F>,R> where F:FnMut(T) -> I, I:IntoIterator, R:Reducer<A,I::Item> {
+    fn step(&mut self, mut acc:A, t:T) -> Result<A,Reduced<A>>
+    {for u in (self.xf.0)(t) {acc = self.rf.step(acc, u)?} Ok(acc)}
+
+    fn complete(&mut self, acc:A) -> A
+    {self.rf.complete(acc)}
+}
+
+impl<A,T,P,R> Reducer<A,T> for Step<Filter<P>,R> where P:FnMut(&T) -> bool, R:Reducer<A,T> {
+    fn step(&mut self, acc:A, t:T) -> Result<A,Reduced<A>>
+    {if (self.xf.0)(&t) {self.rf.step(acc, t)} else {Ok(acc)}}
+
+    fn complete(&mut self, acc:A) -> A
+    {self.rf.complete(acc)}
+}
+
+impl<A,T,P,R> Reducer<A,T> for Step<Remove<P>,R> where P:FnMut(&T) -> bool, R:Reducer<A,T> {
+    fn step(&mut self, acc:A, t:T) -> Result<A,Reduced<A>>
+    {if (self.xf.0)(&t) {Ok(acc)} else {self.rf.step(acc, t)}}
+
+    fn complete(&mut self, acc:A) -> A
+    {self.rf.complete(acc)}
+}
+
+impl<A,T,U,F,R> Reducer<A,T> for Step<Keep<F>,R> where F:FnMut(T) -> Option<U>, R:Reducer<A,U> {
+    fn step(&mut self, acc:A, t:T) -> Result<A,Reduced<A>>
+    {match (self.xf.0)(t) {Some(u) => self.rf.step(acc, u), None => Ok(acc)}}
+
+    fn complete(&mut self, acc:A) -> A
+    {self.rf.complete(acc)}
+}
+
+impl<A,T,R> Reducer<A,T> for Step<Take,R> where R:Reducer<A,T> {
+    fn step(&mut self, acc:A, t:T) -> Result<A,Reduced<A>>
+    {if self.xf.0 == 0 {return Err(Reduced(acc))}
+     self.xf.0 -= 1;
+     let acc = self.rf.step(acc, t)?;
+     if self.xf.0 == 0 {Err(Reduced(acc))} else {Ok(acc)}}
+
+    fn complete(&mut self, acc:A) -> A
+    {self.rf.complete(acc)}
+}
+
+impl<A,T,P,R> Reducer<A,T> for Step<TakeWhile<P>,R> where P:FnMut(&T) -> bool, R:Reducer<A,T> {
+    fn step(&mut self, acc:A, t:T) -> Result<A,Reduced<A>>
+    {if (self.xf.0)(&t) {self.rf.step(acc, t)} else {Err(Reduced(acc))}}
+
+    fn complete(&mut self, acc:A) -> A
+    {self.rf.complete(acc)}
+}
+
+impl<A,T,R> Reducer<A,T> for Step<Drop,R> where R:Reducer<A,T> {
+    fn step(&mut self, acc:A, t:T) -> Result<A,Reduced<A>>
+    {if self.xf.0 > 0 {self.xf.0 -= 1; Ok(acc)} else {self.rf.step(acc, t)}}
+
+    fn complete(&mut self, acc:A) -> A
+    {self.rf.complete(acc)}
+}
+
+impl<A,T,R> Reducer<A,T> for Step<Cat,R> where T:IntoIterator, R:Reducer<A,T::Item> {
+    fn step(&mut self, mut acc:A, t:T) -> Result<A,Reduced<A>>
+    {for u in t {acc = self.rf.step(acc, u)?} Ok(acc)}
+
+    fn complete(&mut self, acc:A) -> A
+    {self.rf.complete(acc)}
+}
+
+/// [`partition_all`](fn.partition_all.html) applied to a reducer.
+pub struct Partitioning<T,R> {n:usize, buf:Vec<T>, rf:R}
+
+impl<A,T,R> Reducer<A,T> for Partitioning<T,R> where R:Reducer<A,Vec<T>> {
+    fn step(&mut self, acc:A, t:T) -> Result<A,Reduced<A>>
+    {self.buf.push(t);
+     if self.buf.len() < self.n {return Ok(acc)}
+     let chunk = mem::replace(&mut self.buf, Vec::with_capacity(self.n));
+     self.rf.step(acc, chunk)}
+
+    fn complete(&mut self, acc:A) -> A
+    {let acc = match self.buf.is_empty()
+     {true => acc,
+      false => match self.rf.step(acc, mem::take(&mut self.buf)) {Ok(a) | Err(Reduced(a)) => a}};
+     self.rf.complete(acc)}
+}
+
+/// [`dedupe`](fn.dedupe.html) applied to a reducer.
+pub struct Deduping<T,R> {prev:Option<T>, rf:R}
+
+impl<A,T,R> Reducer<A,T> for Deduping<T,R> where T:PartialEq + Clone, R:Reducer<A,T> {
+    fn step(&mut self, acc:A, t:T) -> Result<A,Reduced<A>>
+    {if self.prev.as_ref() == Some(&t) {return Ok(acc)}
+     self.prev = Some(t.clone());
+     self.rf.step(acc, t)}
+
+    fn complete(&mut self, acc:A) -> A
+    {self.rf.complete(acc)}
+}
+
+/// [`distinct`](fn.distinct.html) applied to a reducer.
+pub struct Distincting<T,R> {seen:HashSet<T>, rf:R}
+
+impl<A,T,R> Reducer<A,T> for Distincting<T,R> where T:Hash + Eq + Clone, R:Reducer<A,T> {
+    fn step(&mut self, acc:A, t:T) -> Result<A,Reduced<A>>
+    {if self.seen.insert(t.clone()) {self.rf.step(acc, t)} else {Ok(acc)}}
+
+    fn complete(&mut self, acc:A) -> A
+    {self.rf.complete(acc)}
+}
+
+/// [`interpose`](fn.interpose.html) applied to a reducer.
+pub struct Interposing<T,R> {sep:T, started:bool, rf:R}
+
+impl<A,T,R> Reducer<A,T> for Interposing<T,R> where T:Clone, R:Reducer<A,T> {
+    fn step(&mut self, acc:A, t:T) -> Result<A,Reduced<A>>
+    {let acc = if self.started {self.rf.step(acc, self.sep.clone())?} else {self.started = true; acc};
+     self.rf.step(acc, t)}
+
+    fn complete(&mut self, acc:A) -> A
+    {self.rf.complete(acc)}
+}
@@ -0,0 +1,345 @@
+use _map::Map;
+use _set::Set;
+use _seq::{Seq,Vector};
+use vec_sorted_map::VecSortedMap;
+use vec_sorted_set::VecSortedSet;
+use keyword::{Keyword,Symbol};
+use std::collections::VecDeque;
+use std::borrow::Borrow;
+use std::hash::{Hash,Hasher};
+use std::cmp::Ordering;
+use std::mem;
+use std::convert::TryFrom;
+
+/// dynamically typed data, like `clojure`'s.
+///
+/// the order is total: values of different kinds are ordered as the variants
+/// are listed, and floats by
+/// [`total_cmp`](https://doc.rust-lang.org/std/primitive.f64.html#method.total_cmp).
+/// so `NaN` equals itself, `-0.0` is less than `0.0`, and any value can be a
+/// key in a map or an item in a set, including another map or set. unlike in
+/// `clojure`, an int never equals a float, and a list never equals a vector.
+///
+/// `Value` implements [`Map`](../trait.Map.html), [`Set`](../trait.Set.html)
+/// and [`Seq`](../trait.Seq.html), with `nil` acting as the empty collection
+/// like in `clojure`: `Map::inc` on nil makes a map, `Set::inc` a set, and
+/// `Seq::inc` a list. `Seq::inc` adds to the front of a list and to the back
+/// of a vector, and takes a vector of two to add an entry to a map. `Map::inc`
+/// on a vector takes an int index and assocs it. any other combination
+/// panics. as all three protocols are implemented, their methods are called
+/// as `Map::inc(v, k, x)` and so on.
+///
+/// # example
+/// ```
+/// use protocoll::{Map,Seq};
+/// use protocoll::data::Value;
+/// use protocoll::string::Keyword;
+/// let k = |s| Value::Keyword(Keyword::new(s));
+/// let langs = Seq::inc(Value::Vector(vec![]), Value::from("clojure"));
+/// let user = Map::inc(Map::inc(Value::Nil, k("name"), Value::from("ann")), k("langs"), langs);
+/// assert_eq!(user.get(&k("name")).and_then(Value::as_str), Some("ann"));
+/// assert_eq!(user.get(&k("langs")).map(Value::len), Some(1));
+/// let users = Map::inc(Value::Nil, user.clone(), Value::Float(f64::NAN));
+/// assert!(users.get(&user).is_some_and(|v| v == &Value::Float(f64::NAN)));
+/// let l = Seq::inc(Seq::inc(Value::Nil, Value::Int(2)), Value::Int(1));
+/// assert_eq!(l, Value::List(vec![Value::Int(1), Value::Int(2)].into()));
+/// ```
+#[derive(Clone,Debug,Default)]
+pub enum Value {
+    #[default]
+    Nil,
+    Bool(bool),
+    Int(i64),
+    Float(f64),
+    Char(char),
+    Str(String),
+    Keyword(Keyword),
+    Symbol(Symbol),
+    List(VecDeque<Value>),
+    Vector(Vec<Value>),
+    Map(VecSortedMap<Value,Value>),
+    Set(VecSortedSet<Value>),
+    /// a tagged literal like `#inst "2024-01-01"`, as read from
+    /// [`edn`](../edn/index.html) for tags without a handler.
+    Tagged(Symbol, Box<Value>),
+}
+
+impl Value {
+    fn rank(&self) -> u8
+    {match *self
+     {Value::Nil => 0, Value::Bool(_) => 1, Value::Int(_) => 2, Value::Float(_) => 3,
+      Value::Char(_) => 4, Value::Str(_) => 5, Value::Keyword(_) => 6, Value::Symbol(_) => 7,
+      Value::List(_) => 8, Value::Vector(_) => 9, Value::Map(_) => 10, Value::Set(_) => 11,
+      Value::Tagged(..) => 12}}
+
+    /// the name of the kind of value, for error messages.
+    pub fn kind(&self) -> &'static str
+    {["nil","bool","int","float","char","string","keyword","symbol","list","vector","map","set","tagged"][self.rank() as usize]}
+
+    pub fn is_nil(&self) -> bool
+    {matches!(*self, Value::Nil)}
+
+    /// whether this is a collection, or nil.
+    pub fn is_coll(&self) -> bool
+    {matches!(*self, Value::Nil | Value::List(_) | Value::Vector(_) | Value::Map(_) | Value::Set(_))}
+
+    /// `false` for nil and `false`, `true` for everything else.
+    pub fn is_truthy(&self) -> bool
+    {!matches!(*self, Value::Nil | Value::Bool(false))}
+
+    pub fn as_bool(&self) -> Option<bool>
+    {match *self {Value::Bool(b) => Some(b), _ => None}}
+
+    pub fn as_int(&self) -> Option<i64>
+    {match *self {Value::Int(n) => Some(n), _ => None}}
+
+    /// ints are converted too.
+    pub fn as_float(&self) -> Option<f64>
+    {match *self {Value::Float(x) => Some(x), Value::Int(n) => Some(n as f64), _ => None}}
+
+    pub fn as_char(&self) -> Option<char>
+    {match *self {Value::Char(c) => Some(c), _ => None}}
+
+    pub fn as_str(&self) -> Option<&str>
+    {match *self {Value::Str(ref s) => Some(s), _ => None}}
+
+    pub fn as_keyword(&self) -> Option<Keyword>
+    {match *self {Value::Keyword(k) => Some(k), _ => None}}
+
+    pub fn as_symbol(&self) -> Option<Symbol>
+    {match *self {Value::Symbol(s) => Some(s), _ => None}}
+
+    /// the number of items in a collection, 0 for nil, the number of chars in a
+    /// string, and 1 for anything else.
+    pub fn len(&self) -> usize
+    {match *self
+     {Value::Nil => 0,
+      Value::Str(ref s) => s.chars().count(),
+      Value::List(ref l) => l.len(),
+      Value::Vector(ref v) => v.len(),
+      Value::Map(ref m) => m.len(),
+      Value::Set(ref s) => s.len(),
+      _ => 1}}
+
+    pub fn is_empty(&self) -> bool
+    {self.len() == 0}
+
+    /// moves the value out, leaving nil behind.
+    pub fn take(&mut self) -> Value
+    {mem::take(self)}
+
+    /// the value at key `k` in a map, at index `k` in a list or vector, or `k`
+    /// itself in a set.
+    ///
+    /// like `clojure`'s [`get`](http://clojuredocs.org/clojure.core/get).
+    pub fn get(&self, k:&Value) -> Option<&Value>
+    {match (self, k)
+     {(Value::Map(m), _) => m.get(k),
+      (Value::Set(s), _) => s.get(k),
+      (Value::Vector(v), &Value::Int(i)) => usize::try_from(i).ok().and_then(|i| v.get(i)),
+      (Value::List(l), &Value::Int(i)) => usize::try_from(i).ok().and_then(|i| l.get(i)),
+      _ => None}}
+
+    /// follows the keys in `ks` through nested values.
+    ///
+    /// like `clojure`'s [`get-in`](http://clojuredocs.org/clojure.core/get-in).
+    pub fn get_in<'a,I>(&'a self, ks:I) -> Option<&'a Value> where I:IntoIterator<Item = &'a Value>
+    {ks.into_iter().try_fold(self, |v,k| v.get(k))}
+
+    /// iterates over the items of a list, vector or set, the entries of a map
+    /// as vectors of two, and nothing for anything else.
+    pub fn items(&self) -> Box<dyn Iterator<Item = Value> + '_>
+    {match *self
+     {Value::List(ref l) => Box::new(l.iter().cloned()),
+      Value::Vector(ref v) => Box::new(v.iter().cloned()),
+      Value::Set(ref s) => Box::new(s.iter().cloned()),
+      Value::Map(ref m) => Box::new(m.iter().map(|(k,v)| Value::Vector(vec![k.clone(), v.clone()]))),
+      _ => Box::new(None.into_iter())}}
+}
+
+fn unsupported(op:&str, v:&Value) -> !
+{panic!("{} is not supported on {}", op, v.kind())}
+
+impl PartialEq for Value
+{fn eq(&self, other:&Value) -> bool {self.cmp(other) == Ordering::Equal}}
+
+impl Eq for Value {}
+
+impl PartialOrd for Value
+{fn partial_cmp(&self, other:&Value) -> Option<Ordering> {Some(self.cmp(other))}}
+
+impl Ord for Value {
+    fn cmp(&self, other:&Value) -> Ordering {
+        match (self, other) {
+            (Value::Nil, Value::Nil) => Ordering::Equal,
+            (Value::Bool(a), Value::Bool(b)) => a.cmp(b),
+            (Value::Int(a), Value::Int(b)) => a.cmp(b),
+            (Value::Float(a), Value::Float(b)) => a.total_cmp(b),
+            (Value::Char(a), Value::Char(b)) => a.cmp(b),
+            (Value::Str(a), Value::Str(b)) => a.cmp(b),
+            (Value::Keyword(a), Value::Keyword(b)) => a.cmp(b),
+            (Value::Symbol(a), Value::Symbol(b)) => a.cmp(b),
+            (Value::List(a), Value::List(b)) => a.cmp(b),
+            (Value::Vector(a), Value::Vector(b)) => a.cmp(b),
+            (Value::Map(a), Value::Map(b)) => a.cmp(b),
+            (Value::Set(a), Value::Set(b)) => a.cmp(b),
+            (Value::Tagged(s, a), Value::Tagged(t, b)) => (s, a).cmp(&(t, b)),
+            _ => self.rank().cmp(&other.rank())
+        }
+    }
+}
+
+impl Hash for Value {
+    fn hash<H:Hasher>(&self, state:&mut H) {
+        self.rank().hash(state);
+        match *self {
+            Value::Nil => (),
+            Value::Bool(b) => b.hash(state),
+            Value::Int(n) => n.hash(state),
+            Value::Float(x) => x.to_bits().hash(state),
+            Value::Char(c) => c.hash(state),
+            Value::Str(ref s) => s.hash(state),
+            Value::Keyword(k) => k.hash(state),
+            Value::Symbol(s) => s.hash(state),
+            Value::List(ref l) => l.hash(state),
+            Value::Vector(ref v) => v.hash(state),
+            Value::Map(ref m) => m.hash(state),
+            Value::Set(ref s) => s.hash(state),
+            Value::Tagged(t, ref v) => {t.hash(state); v.hash(state)}
+        }
+    }
+}
+
+impl From<bool> for Value
+{fn from(b:bool) -> Value {Value::Bool(b)}}
+
+impl From<i64> for Value
+{fn from(n:i64) -> Value {Value::Int(n)}}
+
+impl From<i32> for Value
+{fn from(n:i32) -> Value {Value::Int(n as i64)}}
+
+impl From<f64> for Value
+{fn from(x:f64) -> Value {Value::Float(x)}}
+
+impl From<char> for Value
+{fn from(c:char) -> Value {Value::Char(c)}}
+
+impl From<String> for Value
+{fn from(s:String) -> Value {Value::Str(s)}}
+
+impl From<&str> for Value
+{fn from(s:&str) -> Value {Value::Str(s.to_owned())}}
+
+impl From<Keyword> for Value
+{fn from(k:Keyword) -> Value {Value::Keyword(k)}}
+
+impl From<Symbol> for Value
+{fn from(s:Symbol) -> Value {Value::Symbol(s)}}
+
+impl From<Vec<Value>> for Value
+{fn from(v:Vec<Value>) -> Value {Value::Vector(v)}}
+
+impl From<VecSortedMap<Value,Value>> for Value
+{fn from(m:VecSortedMap<Value,Value>) -> Value {Value::Map(m)}}
+
+impl From<VecSortedSet<Value>> for Value
+{fn from(s:VecSortedSet<Value>) -> Value {Value::Set(s)}}
+
+impl<T> From<Option<T>> for Value where T:Into<Value>
+{fn from(o:Option<T>) -> Value {o.map_or(Value::Nil, Into::into)}}
+
+impl Map<Value,Value> for Value {
+    fn fun<'a,Q>(&'a self) -> Box<dyn Fn(&Q) -> Option<&'a Value> + 'a> where Value:Borrow<Q>, Q:?Sized+Hash+Ord
+    {match *self {Value::Map(ref m) => Map::fun(m), _ => Box::new(|_| None)}}
+
+    fn inc(self, k:Value, v:Value) -> Self
+    {Map::update(self, k, |_| v)}
+
+    fn dec<Q>(self, k:&Q) -> Self where Value:Borrow<Q>, Q:?Sized+Hash+Ord
+    {match self
+     {Value::Map(m) => Value::Map(Map::dec(m, k)),
+      Value::Nil => Value::Nil,
+      v => unsupported("dissoc", &v)}}
+
+    fn zero(self) -> Self
+    {Seq::zero(self)}
+
+    fn shrink(self) -> Self
+    {Seq::shrink(self)}
+
+    fn update<F>(self, k:Value, f:F) -> Self where F:FnOnce(Option<Value>) -> Value
+    {match (self, k)
+     {(Value::Map(m), k) => Value::Map(Map::update(m, k, f)),
+      (Value::Nil, k) => Value::Map(Map::update(VecSortedMap::new(), k, f)),
+      (Value::Vector(v), Value::Int(i)) if i >= 0 => Value::Vector(Vector::update(v, i as usize, f).unwrap_or_else(|e| panic!("{}", e))),
+      (v, _) => unsupported("assoc", &v)}}
+}
+
+impl Set<Value> for Value {
+    fn fun<'a,Q>(&'a self) -> Box<dyn Fn(&Q) -> Option<&'a Value> + 'a> where Value:Borrow<Q>, Q:?Sized+Hash+Ord
+    {match *self {Value::Set(ref s) => Set::fun(s), _ => Box::new(|_| None)}}
+
+    fn inc(self, i:Value) -> Self
+    {match self
+     {Value::Set(s) => Value::Set(Set::inc(s, i)),
+      Value::Nil => Value::Set(Set::inc(VecSortedSet::new(), i)),
+      v => unsupported("set conj", &v)}}
+
+    fn dec<Q>(self, i:&Q) -> Self where Value:Borrow<Q>, Q:?Sized+Hash+Ord
+    {match self
+     {Value::Set(s) => Value::Set(Set::dec(s, i)),
+      Value::Nil => Value::Nil,
+      v => unsupported("disj", &v)}}
+
+    fn zero(self) -> Self
+    {Seq::zero(self)}
+
+    fn shrink(self) -> Self
+    {Seq::shrink(self)}
+}
+
+impl Seq<Value> for Value {
+    fn fun<'a>(&'a self) -> Box<dyn Fn(usize) -> Option<&'a Value> + 'a>
+    {match *self
+     {Value::List(ref l) => Seq::fun(l),
+      Value::Vector(ref v) => Seq::fun(v),
+      _ => Box::new(|_| None)}}
+
+    fn inc(self, i:Value) -> Self
+    {match self
+     {Value::Nil => Value::List(VecDeque::from(vec![i])),
+      Value::List(mut l) => {l.push_front(i); Value::List(l)}
+      Value::Vector(v) => Value::Vector(Seq::inc(v, i)),
+      Value::Set(s) => Value::Set(Set::inc(s, i)),
+      Value::Map(m) => match i
+      {Value::Vector(mut e) if e.len() == 2 =>
+       {let v = e.pop().unwrap(); let k = e.pop().unwrap(); Value::Map(Map::inc(m, k, v))}
+       i => panic!("conj on a map takes a vector of two, not a {}", i.kind())},
+      v => unsupported("conj", &v)}}
+
+    fn dec(self) -> Self
+    {match self
+     {Value::Nil => Value::Nil,
+      Value::List(l) => Value::List(Seq::dec(l)),
+      Value::Vector(v) => Value::Vector(Seq::dec(v)),
+      v => unsupported("pop", &v)}}
+
+    fn zero(mut self) -> Self
+    {match self
+     {Value::Nil => (),
+      Value::List(ref mut l) => l.clear(),
+      Value::Vector(ref mut v) => v.clear(),
+      Value::Map(ref mut m) => m.clear(),
+      Value::Set(ref mut s) => s.clear(),
+      ref v => unsupported("empty", v)} self}
+
+    fn shrink(mut self) -> Self
+    {match self
+     {Value::List(ref mut l) => l.shrink_to_fit(),
+      Value::Vector(ref mut v) => v.shrink_to_fit(),
+      Value::Map(ref mut m) => m.shrink_to_fit(),
+      Value::Set(ref mut s) => s.shrink_to_fit(),
+      Value::Str(ref mut s) => s.shrink_to_fit(),
+      _ => ()} self}
+}
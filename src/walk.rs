@@ -0,0 +1,278 @@
+use _map::Map;
+use value::Value;
+use json_value::Json;
+use keyword::{Keyword,Symbol};
+use vec_sorted_map::VecSortedMap;
+use vec_sorted_set::VecSortedSet;
+use priority_map::PriorityMap;
+use persistent_deque::PersistentDeque;
+use lazy_seq::LazySeq;
+use min_heap::MinHeap;
+use std::collections::{HashMap,BTreeMap,HashSet,BTreeSet,VecDeque};
+use std::hash::Hash;
+
+/// nested structures whose nodes can all be transformed, each collection
+/// being rebuilt as its own type from the transformed items.
+///
+/// with the dynamic [`Value`](enum.Value.html) and
+/// [`Json`](../json/enum.Json.html) every value is a node, including the
+/// collections, like in `clojure`'s
+/// [`clojure.walk`](http://clojuredocs.org/clojure.walk). with the statically
+/// typed collections only the innermost items are, as the collections in
+/// between are of other types: the items of sequences and sets, and the values
+/// of maps, whose keys stay as they are.
+///
+/// # example
+/// ```
+/// use protocoll::data::{self,Walk};
+/// use protocoll::map::VecSortedMap;
+/// let m:VecSortedMap<_,_> = vec![("a", vec![1,2]), ("b", vec![3])].into_iter().collect();
+/// let m = data::postwalk(|n:i32| n * 10, m);
+/// assert_eq!(m.view_content(), &[("a", vec![10,20]), ("b", vec![30])]);
+/// let mut seen = Vec::new();
+/// Some(vec![1,2]).walk_with(&mut |n| {seen.push(n); n}, &mut |n| n + 1);
+/// assert_eq!(seen, [1,2]);
+/// ```
+pub trait Walk where Self:Sized {
+    /// the type of the nodes.
+    type Node;
+
+    /// transforms each node by `pre` on the way down, before its items are
+    /// walked, and by `post` on the way up, after they have been, all in a
+    /// depth first order.
+    fn walk_with<F,G>(self, pre:&mut F, post:&mut G) -> Self
+        where F:FnMut(Self::Node) -> Self::Node, G:FnMut(Self::Node) -> Self::Node;
+}
+
+macro_rules! leaves {
+    ($($t:ty)*) => {$(
+        impl Walk for $t
+        {type Node = $t;
+         fn walk_with<F,G>(self, pre:&mut F, post:&mut G) -> $t where F:FnMut($t) -> $t, G:FnMut($t) -> $t
+         {post(pre(self))}}
+    )*}
+}
+
+leaves!(bool char i8 i16 i32 i64 isize u8 u16 u32 u64 usize f32 f64 String Keyword Symbol);
+
+impl<T> Walk for Option<T> where T:Walk {
+    type Node = T::Node;
+
+    fn walk_with<F,G>(self, pre:&mut F, post:&mut G) -> Self where F:FnMut(T::Node) -> T::Node, G:FnMut(T::Node) -> T::Node
+    {self.map(|t| t.walk_with(pre, post))}
+}
+
+impl<T> Walk for Vec<T> where T:Walk {
+    type Node = T::Node;
+
+    fn walk_with<F,G>(self, pre:&mut F, post:&mut G) -> Self where F:FnMut(T::Node) -> T::Node, G:FnMut(T::Node) -> T::Node
+    {self.into_iter().map(|t| t.walk_with(pre, post)).collect()}
+}
+
+impl<T> Walk for VecDeque<T> where T:Walk {
+    type Node = T::Node;
+
+    fn walk_with<F,G>(self, pre:&mut F, post:&mut G) -> Self where F:FnMut(T::Node) -> T::Node, G:FnMut(T::Node) -> T::Node
+    {self.into_iter().map(|t| t.walk_with(pre, post)).collect()}
+}
+
+impl<T> Walk for PersistentDeque<T> where T:Walk + Clone {
+    type Node = T::Node;
+
+    fn walk_with<F,G>(self, pre:&mut F, post:&mut G) -> Self where F:FnMut(T::Node) -> T::Node, G:FnMut(T::Node) -> T::Node
+    {self.into_iter().map(|t| t.walk_with(pre, post)).collect()}
+}
+
+/// realizes the whole seq, and gives a realized one.
+impl<T> Walk for LazySeq<T> where T:Walk + Clone + 'static {
+    type Node = T::Node;
+
+    fn walk_with<F,G>(self, pre:&mut F, post:&mut G) -> Self where F:FnMut(T::Node) -> T::Node, G:FnMut(T::Node) -> T::Node
+    {self.into_iter().map(|t| t.walk_with(pre, post)).collect()}
+}
+
+impl<T> Walk for MinHeap<T> where T:Walk + Ord {
+    type Node = T::Node;
+
+    fn walk_with<F,G>(self, pre:&mut F, post:&mut G) -> Self where F:FnMut(T::Node) -> T::Node, G:FnMut(T::Node) -> T::Node
+    {self.into_iter().map(|t| t.walk_with(pre, post)).collect()}
+}
+
+impl<T> Walk for HashSet<T> where T:Walk + Hash + Eq {
+    type Node = T::Node;
+
+    fn walk_with<F,G>(self, pre:&mut F, post:&mut G) -> Self where F:FnMut(T::Node) -> T::Node, G:FnMut(T::Node) -> T::Node
+    {self.into_iter().map(|t| t.walk_with(pre, post)).collect()}
+}
+
+impl<T> Walk for BTreeSet<T> where T:Walk + Ord {
+    type Node = T::Node;
+
+    fn walk_with<F,G>(self, pre:&mut F, post:&mut G) -> Self where F:FnMut(T::Node) -> T::Node, G:FnMut(T::Node) -> T::Node
+    {self.into_iter().map(|t| t.walk_with(pre, post)).collect()}
+}
+
+impl<T> Walk for VecSortedSet<T> where T:Walk + Ord {
+    type Node = T::Node;
+
+    fn walk_with<F,G>(self, pre:&mut F, post:&mut G) -> Self where F:FnMut(T::Node) -> T::Node, G:FnMut(T::Node) -> T::Node
+    {self.into_iter().map(|t| t.walk_with(pre, post)).collect()}
+}
+
+impl<K,V> Walk for HashMap<K,V> where K:Hash + Eq, V:Walk {
+    type Node = V::Node;
+
+    fn walk_with<F,G>(self, pre:&mut F, post:&mut G) -> Self where F:FnMut(V::Node) -> V::Node, G:FnMut(V::Node) -> V::Node
+    {self.into_iter().map(|(k,v)| (k, v.walk_with(pre, post))).collect()}
+}
+
+impl<K,V> Walk for BTreeMap<K,V> where K:Ord, V:Walk {
+    type Node = V::Node;
+
+    fn walk_with<F,G>(self, pre:&mut F, post:&mut G) -> Self where F:FnMut(V::Node) -> V::Node, G:FnMut(V::Node) -> V::Node
+    {self.into_iter().map(|(k,v)| (k, v.walk_with(pre, post))).collect()}
+}
+
+impl<K,V> Walk for VecSortedMap<K,V> where K:Ord, V:Walk {
+    type Node = V::Node;
+
+    fn walk_with<F,G>(self, pre:&mut F, post:&mut G) -> Self where F:FnMut(V::Node) -> V::Node, G:FnMut(V::Node) -> V::Node
+    {self.into_iter().map(|(k,v)| (k, v.walk_with(pre, post))).collect()}
+}
+
+/// walks the priorities.
+impl<K,P> Walk for PriorityMap<K,P> where K:Hash + Eq + Clone, P:Walk + Ord {
+    type Node = P::Node;
+
+    fn walk_with<F,G>(self, pre:&mut F, post:&mut G) -> Self where F:FnMut(P::Node) -> P::Node, G:FnMut(P::Node) -> P::Node
+    {self.into_iter().map(|(k,p)| (k, p.walk_with(pre, post))).collect()}
+}
+
+/// every value is a node: the items of lists, vectors and sets, the keys and
+/// values of maps, and the values of tagged literals.
+impl Walk for Value {
+    type Node = Value;
+
+    fn walk_with<F,G>(self, pre:&mut F, post:&mut G) -> Value where F:FnMut(Value) -> Value, G:FnMut(Value) -> Value {
+        let v = match pre(self) {
+            Value::List(l) => Value::List(l.into_iter().map(|v| v.walk_with(pre, post)).collect()),
+            Value::Vector(v) => Value::Vector(v.into_iter().map(|v| v.walk_with(pre, post)).collect()),
+            Value::Map(m) => Value::Map(m.into_iter().map(|(k,v)| (k.walk_with(pre, post), v.walk_with(pre, post))).collect()),
+            Value::Set(s) => Value::Set(s.into_iter().map(|v| v.walk_with(pre, post)).collect()),
+            Value::Tagged(tag, v) => Value::Tagged(tag, Box::new(v.walk_with(pre, post))),
+            v => v,
+        };
+        post(v)
+    }
+}
+
+/// every value is a node: the items of arrays and the values of objects.
+impl Walk for Json {
+    type Node = Json;
+
+    fn walk_with<F,G>(self, pre:&mut F, post:&mut G) -> Json where F:FnMut(Json) -> Json, G:FnMut(Json) -> Json {
+        let j = match pre(self) {
+            Json::Array(a) => Json::Array(a.into_iter().map(|j| j.walk_with(pre, post)).collect()),
+            Json::Object(o) => Json::Object(o.into_iter().map(|(k,j)| (k, j.walk_with(pre, post))).collect()),
+            j => j,
+        };
+        post(j)
+    }
+}
+
+/// transforms each node of `x` by `f`, before walking into the items of what
+/// it gives.
+///
+/// like `clojure`'s [`prewalk`](http://clojuredocs.org/clojure.walk/prewalk).
+///
+/// # example
+/// ```
+/// use protocoll::{data,edn};
+/// use protocoll::data::Value;
+/// let v = edn::read("[1 (2 3) #{4}]").unwrap();
+/// let flat = data::prewalk(|v| match v {Value::List(l) => Value::Int(l.len() as i64), v => v}, v.clone());
+/// assert_eq!(flat.to_string(), "[1 2 #{4}]");
+/// let mut order = Vec::new();
+/// data::postwalk(|v:Value| {order.push(v.to_string()); v}, v);
+/// assert_eq!(order, ["1", "2", "3", "(2 3)", "4", "#{4}", "[1 (2 3) #{4}]"]);
+/// ```
+pub fn prewalk<T,F>(mut f:F, x:T) -> T where T:Walk, F:FnMut(T::Node) -> T::Node
+{x.walk_with(&mut f, &mut |n| n)}
+
+/// transforms each node of `x` by `f`, after walking into its items.
+///
+/// like `clojure`'s [`postwalk`](http://clojuredocs.org/clojure.walk/postwalk).
+pub fn postwalk<T,F>(mut f:F, x:T) -> T where T:Walk, F:FnMut(T::Node) -> T::Node
+{x.walk_with(&mut |n| n, &mut f)}
+
+/// replaces the nodes of `x` which are keys of `m` by their values, from the
+/// top down, so that the replacements are walked as well.
+///
+/// like `clojure`'s [`prewalk-replace`](http://clojuredocs.org/clojure.walk/prewalk-replace).
+///
+/// # example
+/// ```
+/// use protocoll::{data,edn};
+/// use std::collections::HashMap;
+/// let m:HashMap<_,_> = vec![(edn::read(":a").unwrap(), edn::read("[:b :c]").unwrap()),
+///                           (edn::read(":b").unwrap(), edn::read("1").unwrap())].into_iter().collect();
+/// let v = edn::read("{:x :a, :y [:b]}").unwrap();
+/// assert_eq!(data::prewalk_replace(&m, v.clone()).to_string(), "{:x [1 :c], :y [1]}");
+/// assert_eq!(data::postwalk_replace(&m, v).to_string(), "{:x [:b :c], :y [1]}");
+/// ```
+pub fn prewalk_replace<T,M>(m:&M, x:T) -> T where T:Walk, T::Node:Clone + Hash + Ord, M:Map<T::Node,T::Node>
+{let get = m.fun();
+ prewalk(|n| match get(&n) {Some(r) => r.clone(), None => n}, x)}
+
+/// replaces the nodes of `x` which are keys of `m` by their values, from the
+/// bottom up, so that the replacements are not walked.
+///
+/// like `clojure`'s [`postwalk-replace`](http://clojuredocs.org/clojure.walk/postwalk-replace).
+pub fn postwalk_replace<T,M>(m:&M, x:T) -> T where T:Walk, T::Node:Clone + Hash + Ord, M:Map<T::Node,T::Node>
+{let get = m.fun();
+ postwalk(|n| match get(&n) {Some(r) => r.clone(), None => n}, x)}
+
+/// transforms the keys of all the maps in `v` by `f`.
+///
+/// # example
+/// ```
+/// use protocoll::{data,edn};
+/// let v = edn::read(r#"{"a" 1, "b" [{"c/d" 2}], :e 3}"#).unwrap();
+/// let k = data::keywordize_keys(v);
+/// assert_eq!(k.to_string(), "{:a 1, :b [{:c/d 2}], :e 3}");
+/// assert_eq!(data::stringify_keys(k).to_string(), r#"{"a" 1, "b" [{"d" 2}], "e" 3}"#);
+/// ```
+pub fn walk_keys<F>(mut f:F, v:Value) -> Value where F:FnMut(Value) -> Value
+{postwalk(|v| match v {Value::Map(m) => Value::Map(m.into_iter().map(|(k,v)| (f(k), v)).collect()), v => v}, v)}
+
+/// turns the string keys of all the maps in `v` into keywords, with the
+/// namespace before a `/`.
+///
+/// every key is interned as a [`Keyword`](../string/struct.Keyword.html),
+/// which is never freed, so keys from untrusted input can grow the interner
+/// without bound. to only turn the keys which are already keywords, use
+/// [`walk_keys`](fn.walk_keys.html) with
+/// [`Keyword::find`](../string/struct.Keyword.html#method.find):
+///
+/// ```
+/// use protocoll::{data,edn};
+/// use protocoll::data::Value;
+/// use protocoll::string::Keyword;
+/// Keyword::new("e");
+/// let v = edn::read(r#"{"e" 1, "never-seen-before" 2}"#).unwrap();
+/// let k = data::walk_keys(|k| match k
+///     {Value::Str(s) => Keyword::find(&s).map_or(Value::Str(s), Value::Keyword), k => k}, v);
+/// assert_eq!(k.to_string(), r#"{"never-seen-before" 2, :e 1}"#);
+/// assert_eq!(Keyword::find("never-seen-before"), None);
+/// ```
+///
+/// like `clojure`'s [`keywordize-keys`](http://clojuredocs.org/clojure.walk/keywordize-keys).
+pub fn keywordize_keys(v:Value) -> Value
+{walk_keys(|k| match k {Value::Str(s) => Value::Keyword(Keyword::parse(&s)), k => k}, v)}
+
+/// turns the keyword keys of all the maps in `v` into strings of their
+/// names, without their namespaces.
+///
+/// like `clojure`'s [`stringify-keys`](http://clojuredocs.org/clojure.walk/stringify-keys).
+pub fn stringify_keys(v:Value) -> Value
+{walk_keys(|k| match k {Value::Keyword(k) => Value::Str(k.name().to_string()), k => k}, v)}